@@ -0,0 +1,98 @@
+//! Command Line Arguments
+//!
+//! Parses the flags the game accepts when launched:
+//!
+//! |Flag|Description|
+//! |-|-|
+//! |`--record <file>`| Records every frame's key and delta time to `<file>`|
+//! |`--replay <file>`| Plays back a file written by `--record` instead of reading the keyboard|
+//! |`--width <columns>`| Width of the plane, defaults to the terminal's width|
+//! |`--height <rows>`| Height of the plane, defaults to what fits in the terminal|
+//! |`--config <file>`| Reads the game's tuning from `<file>` instead of `spaceinvaders.toml`|
+//! |`--formations <file>`| Reads the alien types and formations from `<file>` instead of `formations.toml`|
+//! |`--seed <number>`| Seeds the game's random numbers, the same seed plays out the same game|
+use crate::errors::{Error, ErrorKind};
+
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    /// path to write a replay to
+    pub record: Option<String>,
+    /// path to read a replay from
+    pub replay: Option<String>,
+    /// width of the plane
+    pub width: Option<usize>,
+    /// height of the plane
+    pub height: Option<usize>,
+    /// path to read the configuration from
+    pub config: Option<String>,
+    /// path to read the alien formations from
+    pub formations: Option<String>,
+    /// seed of the game's random numbers
+    pub seed: Option<u64>,
+}
+
+impl Args {
+    /// Parses the arguments the process was launched with
+    pub fn parse() -> Result<Self, Error> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    /// Parses arguments, excluding the program name
+    pub fn parse_from<I: IntoIterator<Item = String>>(args: I) -> Result<Self, Error> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record" => parsed.record = Some(value_of(&arg, args.next())?),
+                "--replay" => parsed.replay = Some(value_of(&arg, args.next())?),
+                "--width" => parsed.width = Some(number_of(&arg, args.next())?),
+                "--height" => parsed.height = Some(number_of(&arg, args.next())?),
+                "--config" => parsed.config = Some(value_of(&arg, args.next())?),
+                "--formations" => parsed.formations = Some(value_of(&arg, args.next())?),
+                "--seed" => parsed.seed = Some(number_of(&arg, args.next())? as u64),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidArgument,
+                        format!("Unknown argument `{}`", arg),
+                    ))
+                }
+            }
+        }
+        if parsed.seed.is_some() && parsed.replay.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidArgument,
+                "`--seed` cannot be used with `--replay`, which plays with the recorded seed",
+            ));
+        }
+        if parsed.record.is_some() && parsed.replay.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidArgument,
+                "`--record` and `--replay` cannot be used together",
+            ));
+        }
+        Ok(parsed)
+    }
+}
+
+/// Makes sure a flag was followed by its value
+fn value_of(flag: &str, value: Option<String>) -> Result<String, Error> {
+    value.ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidArgument,
+            format!("`{}` expects a file path", flag),
+        )
+    })
+}
+
+/// Makes sure a flag was followed by a positive whole number
+fn number_of(flag: &str, value: Option<String>) -> Result<usize, Error> {
+    value
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidArgument,
+                format!("`{}` expects a positive whole number", flag),
+            )
+        })
+}
@@ -0,0 +1,847 @@
+//! Game Configuration
+//!
+//! Tuning values, such as how fast every entity moves, loaded once at start-up
+//! from a TOML file into a [`Config`]. Every value has a default, so a file only
+//! needs the keys it wants to change.
+//!
+//! # File Lookup
+//! - The path given with `--config <file>`, which must exist.
+//! - Otherwise `spaceinvaders.toml` in the current directory, if there is one.
+//! - Otherwise the defaults are used.
+//!
+//! # Example
+//! ```toml
+//! [aliens]
+//! columns = 8
+//! step_per_delta = 20.0
+//!
+//! [obstacles]
+//! wait_time = 1.5
+//!
+//! [keys]
+//! move_left = ["left", "a", "h"]
+//! move_right = ["right", "d", "l"]
+//! fire = ["space", "w"]
+//! ```
+//!
+//! Every action under `[keys]` takes a key name or a list of them, see
+//! [`crate::input`] for the names accepted. Actions left out keep their keys.
+//!
+//! Unknown keys, values of the wrong type and values out of range are reported
+//! through [`Error`] with [`ErrorKind::InvalidConfig`], naming the offending key
+//! and line. See `spaceinvaders.example.toml` for every key and its default.
+//!
+//! # Access
+//! Once loaded, the configuration is installed with [`set()`] and read anywhere
+//! through [`get()`], which falls back to the defaults if nothing was installed.
+pub(crate) mod parser;
+
+use crate::errors::{Error, ErrorKind};
+use crate::input::{Action, Key, KeyBindings};
+use parser::{Document, Value};
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// File looked up in the current directory when no path is given
+pub const DEFAULT_CONFIG_PATH: &str = "spaceinvaders.toml";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Returns the installed configuration, or the defaults if none was installed
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Installs the configuration read by [`get()`]. Only the first call has an effect.
+pub fn set(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// number of aliens in each row of the formation
+    pub alien_col_count: usize,
+    pub alien_step_per_delta: f32,
+    /// how many times faster the formation moves once down to its last alien
+    pub alien_max_speed_multiplier: f32,
+    /// shape of the speed-up as the formation thins out, 1 being steady
+    /// and higher values saving most of it for the last few aliens
+    pub alien_speed_curve: f32,
+    /// rows the formation moves down every time it turns at the edge
+    pub alien_descent: usize,
+    /// alien shots per second on the first wave, at most
+    pub alien_fire_rate: f32,
+    /// seconds an alien waits after firing before it can fire again
+    pub alien_fire_cooldown: f32,
+    /// most alien bullets in flight at once on the first wave
+    pub alien_max_bullets: usize,
+    /// chance a shot comes from an alien right above the shooter
+    /// rather than from any alien of the front line
+    pub alien_aim: f32,
+    /// seconds between diving aliens breaking off from the formation
+    pub alien_dive_interval: f32,
+    /// how fast diving aliens swoop down
+    pub alien_dive_step_per_delta: f32,
+    pub bullet_step_per_delta: f32,
+    pub shooter_step_per_delta: f32,
+    /// lives the player starts with
+    pub shooter_lives: usize,
+    /// seconds the shooter can't be hit for after respawning
+    pub shooter_invulnerability: f32,
+    /// seconds the shooter waits between two shots
+    pub shooter_fire_cooldown: f32,
+    /// most of the player's bullets on the plane at once
+    pub shooter_max_bullets: usize,
+    /// if true, the player has a single bullet on the plane at a time, like in the arcade
+    pub shooter_classic_fire: bool,
+    pub speedster_step_per_delta: f32,
+    /// velocity of the obstacles dropped in the end game
+    pub obstacle_speed: f32,
+    /// seconds obstacles wait before dropping
+    pub obstacle_wait_time: f32,
+    /// bunkers standing between the shooter and the aliens
+    pub bunker_count: usize,
+    /// seconds between mystery UFOs
+    pub ufo_interval: f32,
+    pub ufo_step_per_delta: f32,
+    /// chance of an alien shot down by the player dropping a power-up
+    pub powerup_drop_chance: f32,
+    /// seconds a power-up lasts once caught
+    pub powerup_duration: f32,
+    /// how fast power-up capsules fall
+    pub powerup_step_per_delta: f32,
+    /// points for an alien of each formation row, from the top one down
+    pub score_alien_rows: Vec<u64>,
+    /// bonus points for beating the speedster through each stage
+    pub score_speedster_stages: Vec<u64>,
+    pub score_alien_bullet: u64,
+    pub score_snowflake: u64,
+    /// bonuses a UFO can be worth, one is picked at random
+    pub score_ufo: Vec<u64>,
+    /// seconds a hit has to follow the previous one by to raise the combo
+    pub combo_window: f32,
+    pub combo_max_multiplier: u64,
+    /// the speedster comes as a boss every this many waves
+    pub wave_boss_every: usize,
+    /// most rows a formation grows to
+    pub wave_max_rows: usize,
+    /// fraction of the first wave's alien speed added every wave
+    pub wave_speed_increase: f32,
+    /// fraction of the first wave's fire rate added every wave
+    pub wave_fire_rate_increase: f32,
+    /// fraction of the first wave's bullets in flight and aim added, and
+    /// of its fire cooldown taken off, every wave
+    pub wave_aggression_increase: f32,
+    /// keys bound to every action
+    pub keys: KeyBindings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            alien_col_count: 6,
+            alien_step_per_delta: 15.0,
+            alien_max_speed_multiplier: 4.0,
+            alien_speed_curve: 2.0,
+            alien_descent: 1,
+            alien_fire_rate: 5.0,
+            alien_fire_cooldown: 1.5,
+            alien_max_bullets: 3,
+            alien_aim: 0.6,
+            alien_dive_interval: 4.0,
+            alien_dive_step_per_delta: 20.0,
+            bullet_step_per_delta: 9.0,
+            shooter_step_per_delta: if cfg!(target_os = "windows") {
+                30.0
+            } else {
+                90.0
+            },
+            shooter_lives: 3,
+            shooter_invulnerability: 2.0,
+            shooter_fire_cooldown: 0.25,
+            shooter_max_bullets: 3,
+            shooter_classic_fire: false,
+            speedster_step_per_delta: 90.0,
+            obstacle_speed: 65.0,
+            obstacle_wait_time: 2.0,
+            bunker_count: 4,
+            ufo_interval: 25.0,
+            ufo_step_per_delta: 30.0,
+            powerup_drop_chance: 0.1,
+            powerup_duration: 10.0,
+            powerup_step_per_delta: 8.0,
+            score_alien_rows: vec![30, 20, 10],
+            score_speedster_stages: vec![100, 200, 500],
+            score_alien_bullet: 5,
+            score_snowflake: 10,
+            score_ufo: vec![50, 100, 150, 300],
+            combo_window: 1.5,
+            combo_max_multiplier: 5,
+            wave_boss_every: 3,
+            wave_max_rows: 6,
+            wave_speed_increase: 0.15,
+            wave_fire_rate_increase: 0.2,
+            wave_aggression_increase: 0.1,
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the configuration from the given path, or from `spaceinvaders.toml`
+    /// if no path is given and the file exists, otherwise returns the defaults
+    pub fn load(path: Option<&str>) -> Result<Self, Error> {
+        match path {
+            Some(path) => Self::from_file(path),
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => Self::from_file(DEFAULT_CONFIG_PATH),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Reads and validates a configuration file
+    pub fn from_file(path: &str) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            Error::new(
+                ErrorKind::Io,
+                format!("Could not read config `{}`: {}", path, e),
+            )
+        })?;
+        Self::parse(&text)
+            .map_err(|e| Error::new(e.kind(), format!("In `{}`, {}", path, e.diagnosis())))
+    }
+
+    /// Parses and validates the contents of a configuration file
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut document = parser::parse(text)?;
+        let mut config = Self::default();
+        take_count(
+            &mut document,
+            "aliens.columns",
+            1,
+            10,
+            &mut config.alien_col_count,
+        )?;
+        take_speed(
+            &mut document,
+            "aliens.step_per_delta",
+            &mut config.alien_step_per_delta,
+        )?;
+        if let Some((multiplier, line)) = take_f32(&mut document, "aliens.max_speed_multiplier")? {
+            // the formation never slows down as it thins out
+            if multiplier < 1.0 {
+                return Err(invalid(
+                    "aliens.max_speed_multiplier",
+                    line,
+                    format!("must be at least 1, found {}", multiplier),
+                ));
+            }
+            config.alien_max_speed_multiplier = multiplier;
+        }
+        take_speed(
+            &mut document,
+            "aliens.speed_curve",
+            &mut config.alien_speed_curve,
+        )?;
+        take_count(
+            &mut document,
+            "aliens.descent",
+            0,
+            10,
+            &mut config.alien_descent,
+        )?;
+        take_speed(
+            &mut document,
+            "aliens.fire_rate",
+            &mut config.alien_fire_rate,
+        )?;
+        take_seconds(
+            &mut document,
+            "aliens.fire_cooldown",
+            &mut config.alien_fire_cooldown,
+        )?;
+        take_count(
+            &mut document,
+            "aliens.max_bullets",
+            1,
+            50,
+            &mut config.alien_max_bullets,
+        )?;
+        take_probability(&mut document, "aliens.aim", &mut config.alien_aim)?;
+        take_seconds(
+            &mut document,
+            "aliens.dive_interval",
+            &mut config.alien_dive_interval,
+        )?;
+        take_speed(
+            &mut document,
+            "aliens.dive_step_per_delta",
+            &mut config.alien_dive_step_per_delta,
+        )?;
+        take_speed(
+            &mut document,
+            "bullets.step_per_delta",
+            &mut config.bullet_step_per_delta,
+        )?;
+        take_speed(
+            &mut document,
+            "shooter.step_per_delta",
+            &mut config.shooter_step_per_delta,
+        )?;
+        take_count(
+            &mut document,
+            "shooter.lives",
+            1,
+            9,
+            &mut config.shooter_lives,
+        )?;
+        take_seconds(
+            &mut document,
+            "shooter.invulnerability",
+            &mut config.shooter_invulnerability,
+        )?;
+        take_seconds(
+            &mut document,
+            "shooter.fire_cooldown",
+            &mut config.shooter_fire_cooldown,
+        )?;
+        take_count(
+            &mut document,
+            "shooter.max_bullets",
+            1,
+            50,
+            &mut config.shooter_max_bullets,
+        )?;
+        take_bool(
+            &mut document,
+            "shooter.classic_fire",
+            &mut config.shooter_classic_fire,
+        )?;
+        take_speed(
+            &mut document,
+            "speedster.step_per_delta",
+            &mut config.speedster_step_per_delta,
+        )?;
+        take_speed(&mut document, "obstacles.speed", &mut config.obstacle_speed)?;
+        take_seconds(
+            &mut document,
+            "obstacles.wait_time",
+            &mut config.obstacle_wait_time,
+        )?;
+        take_count(
+            &mut document,
+            "bunkers.count",
+            0,
+            10,
+            &mut config.bunker_count,
+        )?;
+        take_seconds(&mut document, "ufo.interval", &mut config.ufo_interval)?;
+        take_speed(
+            &mut document,
+            "ufo.step_per_delta",
+            &mut config.ufo_step_per_delta,
+        )?;
+        take_probability(
+            &mut document,
+            "powerups.drop_chance",
+            &mut config.powerup_drop_chance,
+        )?;
+        take_seconds(
+            &mut document,
+            "powerups.duration",
+            &mut config.powerup_duration,
+        )?;
+        take_speed(
+            &mut document,
+            "powerups.step_per_delta",
+            &mut config.powerup_step_per_delta,
+        )?;
+        take_points_list(
+            &mut document,
+            "score.alien_rows",
+            &mut config.score_alien_rows,
+        )?;
+        take_points_list(
+            &mut document,
+            "score.speedster_stages",
+            &mut config.score_speedster_stages,
+        )?;
+        take_points(
+            &mut document,
+            "score.alien_bullet",
+            &mut config.score_alien_bullet,
+        )?;
+        take_points(
+            &mut document,
+            "score.snowflake",
+            &mut config.score_snowflake,
+        )?;
+        take_points_list(&mut document, "score.ufo", &mut config.score_ufo)?;
+        take_seconds(&mut document, "combo.window", &mut config.combo_window)?;
+        let mut max_multiplier = config.combo_max_multiplier as usize;
+        take_count(
+            &mut document,
+            "combo.max_multiplier",
+            1,
+            100,
+            &mut max_multiplier,
+        )?;
+        config.combo_max_multiplier = max_multiplier as u64;
+        take_count(
+            &mut document,
+            "waves.boss_every",
+            1,
+            100,
+            &mut config.wave_boss_every,
+        )?;
+        take_count(
+            &mut document,
+            "waves.max_rows",
+            3,
+            10,
+            &mut config.wave_max_rows,
+        )?;
+        take_increase(
+            &mut document,
+            "waves.speed_increase",
+            &mut config.wave_speed_increase,
+        )?;
+        take_increase(
+            &mut document,
+            "waves.fire_rate_increase",
+            &mut config.wave_fire_rate_increase,
+        )?;
+        take_increase(
+            &mut document,
+            "waves.aggression_increase",
+            &mut config.wave_aggression_increase,
+        )?;
+        take_bindings(&mut document, &mut config.keys)?;
+        reject_unknown_keys(&document)?;
+        Ok(config)
+    }
+}
+
+pub(crate) fn invalid(key: &str, line: usize, reason: String) -> Error {
+    Error::new(
+        ErrorKind::InvalidConfig,
+        format!("line {}: `{}` {}", line, key, reason),
+    )
+}
+
+/// Reads a number, integers are accepted where floats are expected
+fn take_f32(document: &mut Document, key: &str) -> Result<Option<(f32, usize)>, Error> {
+    match document.take(key) {
+        None => Ok(None),
+        Some((Value::Float(n), line)) => Ok(Some((n as f32, line))),
+        Some((Value::Integer(n), line)) => Ok(Some((n as f32, line))),
+        Some((value, line)) => Err(invalid(
+            key,
+            line,
+            format!("must be a number, found {} `{}`", value.type_name(), value),
+        )),
+    }
+}
+
+/// Reads a velocity, which must be greater than zero
+fn take_speed(document: &mut Document, key: &str, field: &mut f32) -> Result<(), Error> {
+    if let Some((speed, line)) = take_f32(document, key)? {
+        if speed <= 0.0 {
+            return Err(invalid(
+                key,
+                line,
+                format!("must be greater than 0, found {}", speed),
+            ));
+        }
+        *field = speed;
+    }
+    Ok(())
+}
+
+/// Reads a duration in seconds, which can't be negative
+fn take_seconds(document: &mut Document, key: &str, field: &mut f32) -> Result<(), Error> {
+    if let Some((seconds, line)) = take_f32(document, key)? {
+        if seconds < 0.0 {
+            return Err(invalid(
+                key,
+                line,
+                format!("can't be negative, found {}", seconds),
+            ));
+        }
+        *field = seconds;
+    }
+    Ok(())
+}
+
+/// Reads a chance, from 0 for never to 1 for always
+fn take_probability(document: &mut Document, key: &str, field: &mut f32) -> Result<(), Error> {
+    if let Some((probability, line)) = take_f32(document, key)? {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(invalid(
+                key,
+                line,
+                format!("must be between 0 and 1, found {}", probability),
+            ));
+        }
+        *field = probability;
+    }
+    Ok(())
+}
+
+/// Reads how much something grows by every wave, which can't be negative
+fn take_increase(document: &mut Document, key: &str, field: &mut f32) -> Result<(), Error> {
+    if let Some((increase, line)) = take_f32(document, key)? {
+        if increase < 0.0 {
+            return Err(invalid(
+                key,
+                line,
+                format!("can't be negative, found {}", increase),
+            ));
+        }
+        *field = increase;
+    }
+    Ok(())
+}
+
+/// Reads a whole number within `min..=max`
+pub(crate) fn take_count(
+    document: &mut Document,
+    key: &str,
+    min: usize,
+    max: usize,
+    field: &mut usize,
+) -> Result<(), Error> {
+    match document.take(key) {
+        None => Ok(()),
+        Some((Value::Integer(n), line)) => {
+            if n < min as i64 || n > max as i64 {
+                return Err(invalid(
+                    key,
+                    line,
+                    format!("must be between {} and {}, found {}", min, max, n),
+                ));
+            }
+            *field = n as usize;
+            Ok(())
+        }
+        Some((value, line)) => Err(invalid(
+            key,
+            line,
+            format!(
+                "must be a whole number, found {} `{}`",
+                value.type_name(),
+                value
+            ),
+        )),
+    }
+}
+
+/// Reads a switch, `true` or `false`
+fn take_bool(document: &mut Document, key: &str, field: &mut bool) -> Result<(), Error> {
+    match document.take(key) {
+        None => Ok(()),
+        Some((Value::Boolean(b), _)) => {
+            *field = b;
+            Ok(())
+        }
+        Some((value, line)) => Err(invalid(
+            key,
+            line,
+            format!(
+                "must be true or false, found {} `{}`",
+                value.type_name(),
+                value
+            ),
+        )),
+    }
+}
+
+/// Reads a point value, which can't be negative
+fn take_points(document: &mut Document, key: &str, field: &mut u64) -> Result<(), Error> {
+    match document.take(key) {
+        None => Ok(()),
+        Some((value, line)) => {
+            *field = points_of(key, line, &value)?;
+            Ok(())
+        }
+    }
+}
+
+/// Reads a non-empty list of point values
+fn take_points_list(document: &mut Document, key: &str, field: &mut Vec<u64>) -> Result<(), Error> {
+    let (values, line) = match document.take(key) {
+        None => return Ok(()),
+        Some((Value::Array(values), line)) => (values, line),
+        Some((value, line)) => {
+            return Err(invalid(
+                key,
+                line,
+                format!(
+                    "must be a list of points, found {} `{}`",
+                    value.type_name(),
+                    value
+                ),
+            ))
+        }
+    };
+    if values.is_empty() {
+        return Err(invalid(key, line, "can't be empty".to_string()));
+    }
+    *field = values
+        .iter()
+        .map(|value| points_of(key, line, value))
+        .collect::<Result<Vec<u64>, Error>>()?;
+    Ok(())
+}
+
+fn points_of(key: &str, line: usize, value: &Value) -> Result<u64, Error> {
+    match value {
+        Value::Integer(n) if *n >= 0 => Ok(*n as u64),
+        value => Err(invalid(
+            key,
+            line,
+            format!(
+                "points must be whole numbers of at least 0, found `{}`",
+                value
+            ),
+        )),
+    }
+}
+
+/// Reads the keys bound to every action under `[keys]`
+fn take_bindings(document: &mut Document, bindings: &mut KeyBindings) -> Result<(), Error> {
+    for action in Action::ALL {
+        let key = format!("keys.{}", action);
+        let (names, line) = match document.take(&key) {
+            None => continue,
+            Some((Value::String(name), line)) => (vec![Value::String(name)], line),
+            Some((Value::Array(names), line)) => (names, line),
+            Some((value, line)) => {
+                return Err(invalid(
+                    &key,
+                    line,
+                    format!(
+                        "must be a key name or a list of them, found {} `{}`",
+                        value.type_name(),
+                        value
+                    ),
+                ))
+            }
+        };
+        let mut keys: Vec<Key> = Vec::new();
+        for name in names {
+            match &name {
+                Value::String(s) => match Key::from_name(s) {
+                    Some(k) => keys.push(k),
+                    None => return Err(invalid(&key, line, format!("has an unknown key `{}`", s))),
+                },
+                value => {
+                    return Err(invalid(
+                        &key,
+                        line,
+                        format!("must only hold key names, found {}", value.type_name()),
+                    ))
+                }
+            }
+        }
+        bindings
+            .bind(action, keys)
+            .map_err(|_| invalid(&key, line, "can't be empty".to_string()))?;
+    }
+    if let Some((key, action, other)) = bindings.conflict() {
+        return Err(Error::new(
+            ErrorKind::InvalidConfig,
+            format!("`{}` is bound to both `{}` and `{}`", key, action, other),
+        ));
+    }
+    Ok(())
+}
+
+/// Every known key has been taken by now, anything left is most likely a typo
+pub(crate) fn reject_unknown_keys(document: &Document) -> Result<(), Error> {
+    match document.keys().next() {
+        Some((key, line)) => Err(Error::new(
+            ErrorKind::InvalidConfig,
+            format!("line {}: unknown key `{}`", line, key),
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a configuration expected to be rejected, returning the diagnosis
+    fn rejected(text: &str) -> String {
+        let error = Config::parse(text).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidConfig);
+        error.diagnosis()
+    }
+
+    #[test]
+    fn empty_file_keeps_the_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert_eq!(Config::parse("# nothing\n\n").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn values_override_the_defaults() {
+        let config = Config::parse(
+            "[aliens]\ncolumns = 8\nstep_per_delta = 20\n\n[shooter]\nclassic_fire = true\n",
+        )
+        .unwrap();
+        assert_eq!(config.alien_col_count, 8);
+        assert_eq!(config.alien_step_per_delta, 20.0);
+        assert!(config.shooter_classic_fire);
+        assert_eq!(
+            config.bullet_step_per_delta,
+            Config::default().bullet_step_per_delta
+        );
+    }
+
+    #[test]
+    fn counts_must_be_whole_numbers_within_range() {
+        assert_eq!(
+            rejected("[aliens]\ncolumns = 0"),
+            "line 2: `aliens.columns` must be between 1 and 10, found 0"
+        );
+        assert_eq!(
+            rejected("[aliens]\ncolumns = 11"),
+            "line 2: `aliens.columns` must be between 1 and 10, found 11"
+        );
+        assert_eq!(
+            rejected("[aliens]\ncolumns = -3"),
+            "line 2: `aliens.columns` must be between 1 and 10, found -3"
+        );
+        assert_eq!(
+            rejected("[aliens]\ncolumns = 2.5"),
+            "line 2: `aliens.columns` must be a whole number, found a float `2.5`"
+        );
+        assert_eq!(
+            Config::parse("[bunkers]\ncount = 0").unwrap().bunker_count,
+            0
+        );
+    }
+
+    #[test]
+    fn speeds_must_be_positive_numbers() {
+        assert_eq!(
+            rejected("[bullets]\nstep_per_delta = 0"),
+            "line 2: `bullets.step_per_delta` must be greater than 0, found 0"
+        );
+        assert_eq!(
+            rejected("[bullets]\nstep_per_delta = -1.5"),
+            "line 2: `bullets.step_per_delta` must be greater than 0, found -1.5"
+        );
+        assert_eq!(
+            rejected("[bullets]\nstep_per_delta = \"fast\""),
+            "line 2: `bullets.step_per_delta` must be a number, found a string `\"fast\"`"
+        );
+    }
+
+    #[test]
+    fn durations_and_increases_can_be_zero_but_not_negative() {
+        let config = Config::parse("[ufo]\ninterval = 0\n[waves]\nspeed_increase = 0").unwrap();
+        assert_eq!(config.ufo_interval, 0.0);
+        assert_eq!(config.wave_speed_increase, 0.0);
+        assert_eq!(
+            rejected("[ufo]\ninterval = -1"),
+            "line 2: `ufo.interval` can't be negative, found -1"
+        );
+        assert_eq!(
+            rejected("[waves]\nspeed_increase = -0.5"),
+            "line 2: `waves.speed_increase` can't be negative, found -0.5"
+        );
+    }
+
+    #[test]
+    fn chances_are_between_zero_and_one() {
+        assert_eq!(Config::parse("[aliens]\naim = 1").unwrap().alien_aim, 1.0);
+        assert_eq!(
+            rejected("[aliens]\naim = 1.5"),
+            "line 2: `aliens.aim` must be between 0 and 1, found 1.5"
+        );
+        assert_eq!(
+            rejected("[aliens]\naim = -0.1"),
+            "line 2: `aliens.aim` must be between 0 and 1, found -0.1"
+        );
+    }
+
+    #[test]
+    fn formation_never_slows_down() {
+        assert_eq!(
+            rejected("[aliens]\nmax_speed_multiplier = 0.5"),
+            "line 2: `aliens.max_speed_multiplier` must be at least 1, found 0.5"
+        );
+    }
+
+    #[test]
+    fn switches_must_be_booleans() {
+        assert_eq!(
+            rejected("[shooter]\nclassic_fire = 1"),
+            "line 2: `shooter.classic_fire` must be true or false, found an integer `1`"
+        );
+    }
+
+    #[test]
+    fn points_must_be_whole_and_not_negative() {
+        let config = Config::parse("[score]\nufo = [0, 1000]\nsnowflake = 0").unwrap();
+        assert_eq!(config.score_ufo, vec![0, 1000]);
+        assert_eq!(config.score_snowflake, 0);
+        assert_eq!(
+            rejected("[score]\nufo = []"),
+            "line 2: `score.ufo` can't be empty"
+        );
+        assert_eq!(
+            rejected("[score]\nufo = [50, -1]"),
+            "line 2: `score.ufo` points must be whole numbers of at least 0, found `-1`"
+        );
+        assert_eq!(
+            rejected("[score]\nsnowflake = 2.5"),
+            "line 2: `score.snowflake` points must be whole numbers of at least 0, found `2.5`"
+        );
+        assert_eq!(
+            rejected("[score]\nufo = 50"),
+            "line 2: `score.ufo` must be a list of points, found an integer `50`"
+        );
+    }
+
+    #[test]
+    fn keys_are_bound_by_name() {
+        let config = Config::parse("[keys]\nfire = \"w\"\nmove_left = [\"left\", \"a\"]").unwrap();
+        assert_eq!(
+            config.keys.keys_for(Action::Fire),
+            [Key::from_name("w").unwrap()]
+        );
+        assert_eq!(
+            config.keys.keys_for(Action::MoveLeft),
+            [
+                Key::from_name("left").unwrap(),
+                Key::from_name("a").unwrap()
+            ]
+        );
+        assert_eq!(
+            rejected("[keys]\nfire = \"nope\""),
+            "line 2: `keys.fire` has an unknown key `nope`"
+        );
+        assert_eq!(
+            rejected("[keys]\nfire = []"),
+            "line 2: `keys.fire` can't be empty"
+        );
+        assert_eq!(
+            rejected("[keys]\nfire = [1]"),
+            "line 2: `keys.fire` must only hold key names, found an integer"
+        );
+        assert!(rejected("[keys]\nfire = \"left\"").contains("is bound to both"));
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert_eq!(
+            rejected("[aliens]\ncolumns = 8\ncolums = 8"),
+            "line 3: unknown key `aliens.colums`"
+        );
+        assert_eq!(rejected("speed = 1"), "line 1: unknown key `speed`");
+    }
+}
@@ -0,0 +1,412 @@
+//! A Small TOML Parser
+//!
+//! Parses the subset of TOML the game's configuration needs, without pulling in
+//! any dependency:
+//!
+//! - `[section]` headers, keys inside a section are stored as `section.key`
+//! - `key = value` pairs with bare keys
+//! - strings (`"..."` with `\"`, `\\`, `\n` and `\t` escapes), integers, floats,
+//!   booleans and arrays, which may span several lines
+//! - `#` comments
+//!
+//! Anything else, e.g. inline tables or dates, is reported as an error together
+//! with the line it was found on.
+use crate::errors::{Error, ErrorKind};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// Name of the value's type, used in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "a string",
+            Value::Integer(_) => "an integer",
+            Value::Float(_) => "a float",
+            Value::Boolean(_) => "a boolean",
+            Value::Array(_) => "an array",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{}", n),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// Every key of a parsed document with its value and the line it was defined on
+#[derive(Debug, Clone, Default)]
+pub struct Document {
+    entries: BTreeMap<String, (Value, usize)>,
+}
+
+impl Document {
+    /// Removes a key from the document, returning its value and line
+    pub fn take(&mut self, key: &str) -> Option<(Value, usize)> {
+        self.entries.remove(key)
+    }
+
+    /// Returns the keys that haven't been taken yet, with the line they're on
+    pub fn keys(&self) -> impl Iterator<Item = (&String, usize)> {
+        self.entries.iter().map(|(key, (_, line))| (key, *line))
+    }
+}
+
+/// Walks over the text, keeping track of the current line
+struct Cursor<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn error<T: AsRef<str>>(&self, message: T) -> Error {
+        Error::new(
+            ErrorKind::InvalidConfig,
+            format!("line {}: {}", self.line, message.as_ref()),
+        )
+    }
+
+    /// Skips spaces and tabs, staying on the same line
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t') | Some('\r')) {
+            self.next();
+        }
+    }
+
+    /// Skips a comment up to, but excluding, the end of the line
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.next();
+            }
+        }
+    }
+
+    /// Skips whitespace, new lines and comments
+    fn skip_all(&mut self) {
+        loop {
+            self.skip_blank();
+            self.skip_comment();
+            if self.peek() == Some('\n') {
+                self.next();
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Makes sure nothing but a comment is left on the line
+    fn expect_end_of_line(&mut self) -> Result<(), Error> {
+        self.skip_blank();
+        self.skip_comment();
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.next();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("unexpected `{}`", c))),
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String, Error> {
+        let mut key = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                key.push(c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+        if key.is_empty() {
+            return Err(self.error("expected a key"));
+        }
+        Ok(key)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, Error> {
+        match self.peek() {
+            Some('"') => self.parse_string(),
+            Some('[') => self.parse_array(),
+            Some(c) if c == '-' || c == '+' || c.is_ascii_digit() => self.parse_number(),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let word = self.parse_key()?;
+                match word.as_str() {
+                    "true" => Ok(Value::Boolean(true)),
+                    "false" => Ok(Value::Boolean(false)),
+                    _ => Err(self.error(format!("unexpected `{}`", word))),
+                }
+            }
+            Some(c) => Err(self.error(format!("unexpected `{}`", c))),
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<Value, Error> {
+        self.next(); // opening quote
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(Value::String(s)),
+                Some('\\') => match self.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some(c) => return Err(self.error(format!("unknown escape `\\{}`", c))),
+                    None => return Err(self.error("unterminated string")),
+                },
+                Some('\n') | None => return Err(self.error("unterminated string")),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Value, Error> {
+        let mut number = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.' | '_') {
+                if c != '_' {
+                    number.push(c);
+                }
+                self.next();
+            } else {
+                break;
+            }
+        }
+        if let Ok(n) = number.parse::<i64>() {
+            return Ok(Value::Integer(n));
+        }
+        match number.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(Value::Float(n)),
+            _ => Err(self.error(format!("`{}` is not a number", number))),
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Value, Error> {
+        self.next(); // opening bracket
+        let mut values: Vec<Value> = Vec::new();
+        loop {
+            self.skip_all();
+            if self.peek() == Some(']') {
+                self.next();
+                return Ok(Value::Array(values));
+            }
+            values.push(self.parse_value()?);
+            self.skip_all();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Value::Array(values)),
+                Some(c) => return Err(self.error(format!("expected `,` or `]`, found `{}`", c))),
+                None => return Err(self.error("unterminated array")),
+            }
+        }
+    }
+}
+
+/// Parses a whole document
+pub fn parse(text: &str) -> Result<Document, Error> {
+    let mut cursor = Cursor {
+        chars: text.chars().peekable(),
+        line: 1,
+    };
+    let mut document = Document::default();
+    let mut section = String::new();
+    loop {
+        cursor.skip_all();
+        match cursor.peek() {
+            None => return Ok(document),
+            Some('[') => {
+                cursor.next();
+                if cursor.peek() == Some('[') {
+                    return Err(cursor.error("arrays of tables are not supported"));
+                }
+                cursor.skip_blank();
+                let mut name = cursor.parse_key()?;
+                while cursor.peek() == Some('.') {
+                    cursor.next();
+                    name = format!("{}.{}", name, cursor.parse_key()?);
+                }
+                cursor.skip_blank();
+                if cursor.next() != Some(']') {
+                    return Err(cursor.error("expected `]` closing the section"));
+                }
+                section = name;
+                cursor.expect_end_of_line()?;
+            }
+            Some(_) => {
+                let line = cursor.line;
+                let key = cursor.parse_key()?;
+                cursor.skip_blank();
+                if cursor.next() != Some('=') {
+                    return Err(cursor.error(format!("expected `=` after `{}`", key)));
+                }
+                cursor.skip_blank();
+                let value = cursor.parse_value()?;
+                cursor.expect_end_of_line()?;
+                let full_key = match section.is_empty() {
+                    true => key,
+                    false => format!("{}.{}", section, key),
+                };
+                if document.entries.contains_key(&full_key) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidConfig,
+                        format!("line {}: `{}` is defined twice", line, full_key),
+                    ));
+                }
+                document.entries.insert(full_key, (value, line));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a document expected to be rejected, returning the diagnosis
+    fn rejected(text: &str) -> String {
+        let error = parse(text).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidConfig);
+        error.diagnosis()
+    }
+
+    fn value(text: &str) -> Value {
+        parse(&format!("key = {}", text))
+            .unwrap()
+            .take("key")
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn keys_are_stored_under_their_section() {
+        let mut document =
+            parse("top = 1\n\n[aliens]\ncolumns = 8 # a comment\n[a.b]\nc = 2").unwrap();
+        assert_eq!(document.take("top"), Some((Value::Integer(1), 1)));
+        assert_eq!(
+            document.take("aliens.columns"),
+            Some((Value::Integer(8), 4))
+        );
+        assert_eq!(document.take("a.b.c"), Some((Value::Integer(2), 6)));
+        assert_eq!(document.keys().next(), None);
+    }
+
+    #[test]
+    fn values_of_every_type() {
+        assert_eq!(value("-12"), Value::Integer(-12));
+        assert_eq!(value("+1_000"), Value::Integer(1000));
+        assert_eq!(value("2.5"), Value::Float(2.5));
+        assert_eq!(value("1e3"), Value::Float(1000.0));
+        assert_eq!(value("true"), Value::Boolean(true));
+        assert_eq!(value("false"), Value::Boolean(false));
+        assert_eq!(value("\"space\""), Value::String("space".to_string()));
+        assert_eq!(value("[]"), Value::Array(vec![]));
+        assert_eq!(
+            value("[1, \"a\", [true]]"),
+            Value::Array(vec![
+                Value::Integer(1),
+                Value::String("a".to_string()),
+                Value::Array(vec![Value::Boolean(true)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn arrays_span_lines_with_comments_and_a_trailing_comma() {
+        let mut document = parse("list = [\n  1, # one\n  2,\n]\nnext = 3").unwrap();
+        assert_eq!(
+            document.take("list"),
+            Some((Value::Array(vec![Value::Integer(1), Value::Integer(2)]), 1))
+        );
+        assert_eq!(document.take("next"), Some((Value::Integer(3), 5)));
+    }
+
+    #[test]
+    fn strings_unescape() {
+        assert_eq!(
+            value(r#""a \"b\" \\ \n \t""#),
+            Value::String("a \"b\" \\ \n \t".to_string())
+        );
+        assert_eq!(
+            value("\"# not a comment\""),
+            Value::String("# not a comment".to_string())
+        );
+    }
+
+    #[test]
+    fn bad_strings_are_rejected() {
+        assert_eq!(rejected(r#"key = "\q""#), "line 1: unknown escape `\\q`");
+        assert_eq!(rejected("key = \"open"), "line 1: unterminated string");
+        assert!(rejected("key = \"open\nnext = 1").ends_with("unterminated string"));
+        assert_eq!(rejected("key = \"\\"), "line 1: unterminated string");
+    }
+
+    #[test]
+    fn malformed_documents_are_rejected() {
+        assert_eq!(
+            rejected("key = 1\nkey = 2"),
+            "line 2: `key` is defined twice"
+        );
+        assert_eq!(
+            rejected("[[tables]]"),
+            "line 1: arrays of tables are not supported"
+        );
+        assert_eq!(
+            rejected("[open"),
+            "line 1: expected `]` closing the section"
+        );
+        assert_eq!(rejected("key 1"), "line 1: expected `=` after `key`");
+        assert_eq!(rejected("= 1"), "line 1: expected a key");
+        assert_eq!(rejected("key ="), "line 1: expected a value");
+        assert_eq!(rejected("key = { a = 1 }"), "line 1: unexpected `{`");
+        assert_eq!(rejected("key = yes"), "line 1: unexpected `yes`");
+        assert_eq!(rejected("key = 1 2"), "line 1: unexpected `2`");
+        assert_eq!(rejected("key = 1.2.3"), "line 1: `1.2.3` is not a number");
+        assert_eq!(
+            rejected("key = [1 2]"),
+            "line 1: expected `,` or `]`, found `2`"
+        );
+        assert_eq!(rejected("key = [1,"), "line 1: expected a value");
+    }
+}
@@ -0,0 +1,65 @@
+//! Simulation Clock
+//!
+//! A clock that only moves forward when the simulation is updated, so entities
+//! that wait before acting do so in game time rather than wall-clock time.
+//!
+//! # Components
+//! - [`Clock`]: Total simulated time, owned by the `Engine` and advanced once per update.
+//! - [`Timer`]: A stopwatch started at some point of the simulation, measured against a `Clock`.
+//!
+//! # Example
+//! ```rust
+//! use crate::engine::clock::{Clock, Timer};
+//!
+//! let mut clock = Clock::new();
+//! let timer = Timer::start(&clock);
+//! clock.advance(0.5);
+//! assert_eq!(timer.elapsed(&clock), 0.5);
+//! ```
+
+/// Total time simulated so far, in seconds
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Clock {
+    elapsed: f64,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self { elapsed: 0.0 }
+    }
+
+    /// Returns the simulated seconds since the clock was created
+    pub fn now(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Moves the clock forward by the given amount of seconds
+    pub fn advance(&mut self, delta_time: f32) {
+        self.elapsed += delta_time as f64;
+    }
+}
+
+/// Measures simulated time since it was started or last reset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timer {
+    started: f64,
+}
+
+impl Timer {
+    /// Starts a timer at the clock's current time
+    pub fn start(clock: &Clock) -> Self {
+        Self {
+            started: clock.now(),
+        }
+    }
+
+    /// Returns the simulated seconds since the timer was started
+    pub fn elapsed(&self, clock: &Clock) -> f32 {
+        (clock.now() - self.started) as f32
+    }
+
+    /// Restarts the timer at the clock's current time
+    pub fn reset(&mut self, clock: &Clock) {
+        self.started = clock.now();
+    }
+}
@@ -2,16 +2,24 @@
 //!
 //! This module provides a customizable ASCII-based rendering engine. It simulates a
 //! pixel grid using a matrix of `PixelState` values, where each "pixel" can be either
-//! `On` or `Off`. The engine is designed to support rendering operations, simple sprite
-//! positioning, and pixel-level manipulation, making it suitable for terminal-based
-//! games, visualization tools, or educational graphics demos.
+//! `On`, carrying the [`Glyph`] it is drawn with, or `Off`. The engine is designed to
+//! support rendering operations, simple sprite positioning, and pixel-level manipulation,
+//! making it suitable for terminal-based games, visualization tools, or educational
+//! graphics demos.
 //!
 //! # Features
 //! - Fixed-size grid engine with toggleable pixel states
+//! - Per-pixel glyphs, falling back to a single pixel character when none is given
+//! - Per-pixel foreground and background colors, with a monochrome display for
+//!   terminals that don't support them
 //! - Simple collision toggle system
+//! - Z-ordered layers, each taking part in collisions or not, see [Layers](#layers)
+//! - The [`EntityId`] of the sprite owning every "pixel", so a collision tells what was run into
 //! - Support for both `Rc<RefCell<...>>` and `Arc<RwLock<...>>` wrapped references for flexible ownership models
 //! - ASCII display output for terminal rendering
 //! - Matrix reset, clear, and pixel swapping functionalities
+//! - Dirty "pixel" tracking, so renderers only redraw what changed since the last frame
+//! - A simulation [`Clock`] that entities measure time against
 //!
 //! # Example
 //! ```
@@ -23,21 +31,105 @@
 //! ```
 //!
 //! This will render a 10x5 grid with a single `#` character at position (2, 3).
+//!
+//! # Layers
+//! The engine starts with a single layer, [`Engine::PLANE`], which every sprite is on
+//! unless given another one. More are stacked with [`Engine::add_layer`], each with:
+//! - A z-order: where two layers have a "pixel" on, the one with the higher z is drawn.
+//! - Collisions: sprites on colliding layers run into the "pixels" of every colliding
+//!   layer, sprites on the other layers run into nothing and nothing runs into them.
+//!
+//! ```
+//! let mut engine = Engine::new((10, 5));
+//! // drawn over the plane, e.g. text, without getting in the way of any sprite
+//! let overlay = engine.add_layer(1, false);
+//! engine.spawn_owned(overlay, (2, 3), Glyph::new('!'), EntityId::ANONYMOUS);
+//! assert!(!engine.is_blocked(&(2, 3)));
+//! ```
 use crate::engine::bounding_box::BoundingBox;
+use crate::engine::clock::Clock;
 use crate::engine::sprite;
 
+use crossterm::style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor};
+
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
 pub type Coordinate = (usize, usize);
 
+/// How a "pixel" is drawn, every field left as `None` falls back
+/// to what the display was asked to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Glyph {
+    /// character to draw, `None` draws the default pixel character
+    pub ch: Option<char>,
+    /// foreground color
+    pub fg: Option<Color>,
+    /// background color
+    pub bg: Option<Color>,
+}
+
+impl Glyph {
+    /// Initializer function, a glyph drawn with the given character and no colors
+    pub const fn new(ch: char) -> Self {
+        Self {
+            ch: Some(ch),
+            fg: None,
+            bg: None,
+        }
+    }
+
+    /// Builder function replacing the character, keeping the colors
+    pub const fn with_char(mut self, ch: char) -> Self {
+        self.ch = Some(ch);
+        self
+    }
+
+    /// Builder function setting the foreground color
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Builder function setting the background color
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+}
+
+/// Index of a layer of the engine
+pub type LayerId = usize;
+
+/// Identifies the sprite owning a "pixel", handed out by [`Engine::new_entity`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntityId(usize);
+
+impl EntityId {
+    /// Owner of the "pixels" spawned without a sprite, and of those that are off
+    pub const ANONYMOUS: EntityId = EntityId(0);
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PixelState {
-    On,
+    /// holds the glyph to draw the "pixel" with
+    On(Glyph),
     Off,
 }
 
+/// A matrix of "pixels" stacked with the others of the engine
+#[derive(Debug, Clone)]
+struct Layer {
+    /// layers with a higher z are drawn over the ones with a lower z
+    z: i32,
+    /// if false, sprites on the layer overlap everything and everything overlaps them
+    collides: bool,
+    matrix: Vec<Vec<PixelState>>,
+    /// mirrors `matrix`, the entity owning every "pixel"
+    owners: Vec<Vec<EntityId>>,
+}
+
 /// The engine responsible for rendering the plane
 #[derive(Debug, Clone)]
 pub struct Engine {
@@ -47,11 +139,24 @@ pub struct Engine {
     pub height: usize,
     /// width of the plane
     pub width: usize,
-    /// The matrix representing the plane
-    matrix: Vec<Vec<PixelState>>,
+    /// every layer, indexed by `LayerId`, the plane being the first
+    layers: Vec<Layer>,
+    /// the layers from the top one drawn to the bottom one
+    draw_order: Vec<LayerId>,
+    /// handed out to the next entity asking for an id
+    next_entity: usize,
+    /// simulated time, only advanced by the game's update loop
+    clock: Clock,
+    /// "pixels" that changed since the dirty list was last taken
+    dirty: Vec<Coordinate>,
+    /// mirrors `dirty`, avoiding duplicated coordinates
+    is_dirty: Vec<Vec<bool>>,
 }
 
 impl Engine {
+    /// The layer every sprite is on unless given another one, at z 0 and colliding
+    pub const PLANE: LayerId = 0;
+
     /// initializer function
     ///
     /// takes in a tuple of `(usize, usize)` integers.
@@ -69,7 +174,17 @@ impl Engine {
             collisions: true,
             height,
             width,
-            matrix,
+            layers: vec![Layer {
+                z: 0,
+                collides: true,
+                matrix,
+                owners: vec![vec![EntityId::ANONYMOUS; width]; height],
+            }],
+            draw_order: vec![Self::PLANE],
+            next_entity: 1,
+            clock: Clock::new(),
+            dirty: Vec::new(),
+            is_dirty: vec![vec![false; width]; height],
         }
     }
 
@@ -86,6 +201,64 @@ impl Engine {
         self.collisions
     }
 
+    /// Stacks a new, empty layer at the given z, drawn over the layers with
+    /// a lower z and under those with a higher one. Layers sharing a z are
+    /// drawn in the order they were added, the last one on top
+    pub fn add_layer(&mut self, z: i32, collides: bool) -> LayerId {
+        let layer = self.layers.len();
+        self.layers.push(Layer {
+            z,
+            collides,
+            matrix: vec![vec![PixelState::Off; self.width]; self.height],
+            owners: vec![vec![EntityId::ANONYMOUS; self.width]; self.height],
+        });
+        self.draw_order.push(layer);
+        let layers = &self.layers;
+        self.draw_order
+            .sort_by_key(|&l| (std::cmp::Reverse(layers[l].z), std::cmp::Reverse(l)));
+        layer
+    }
+
+    /// Returns true if sprites on the layer take part in collisions
+    pub fn collides(&self, layer: LayerId) -> bool {
+        self.collisions && self.layers[layer].collides
+    }
+
+    /// Returns true if a "pixel" of any colliding layer is on at the coordinate
+    pub fn is_blocked(&self, coordinate: &Coordinate) -> bool {
+        self.blocker(coordinate).is_some()
+    }
+
+    /// Returns the entity owning the "pixel" of a colliding layer
+    /// that is on at the coordinate, `None` if there's none
+    pub fn blocker(&self, coordinate: &Coordinate) -> Option<EntityId> {
+        if !self.collisions {
+            return None;
+        }
+        let (x, y) = *coordinate;
+        self.layers
+            .iter()
+            .find(|layer| layer.collides && layer.matrix[y][x] != PixelState::Off)
+            .map(|layer| layer.owners[y][x])
+    }
+
+    /// Hands out an id no other entity of the engine has
+    pub fn new_entity(&mut self) -> EntityId {
+        let id = EntityId(self.next_entity);
+        self.next_entity += 1;
+        id
+    }
+
+    /// Returns a copy of the simulation clock
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+
+    /// Moves the simulation clock forward, should be called once per update
+    pub fn advance_clock(&mut self, delta_time: f32) {
+        self.clock.advance(delta_time);
+    }
+
     /// Returns self as a Reference Counted pointer for shared access
     pub fn as_rc(self) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(self))
@@ -96,36 +269,106 @@ impl Engine {
         Arc::new(RwLock::new(self))
     }
 
-    /// Given a coordinate, this function returns the state of the "pixel"
+    /// Given a coordinate, this function returns the state of the "pixel" on the plane
     pub fn is_on(&self, coordinate: &Coordinate) -> bool {
-        self.matrix[coordinate.1][coordinate.0] == PixelState::On
+        matches!(self.pixel(coordinate), PixelState::On(_))
     }
 
-    /// Given a coordinate, this function returns the state of the "pixel"
+    /// Given a coordinate, this function returns the state of the "pixel" on the plane
     pub fn is_off(&self, coordinate: &Coordinate) -> bool {
-        self.matrix[coordinate.1][coordinate.0] == PixelState::Off
+        self.pixel(coordinate) == PixelState::Off
+    }
+
+    /// Given a coordinate, this function returns the state of the "pixel" on the plane
+    pub fn pixel(&self, coordinate: &Coordinate) -> PixelState {
+        self.pixel_on(Self::PLANE, coordinate)
+    }
+
+    /// Given a layer and a coordinate, this function returns the state of the "pixel"
+    pub fn pixel_on(&self, layer: LayerId, coordinate: &Coordinate) -> PixelState {
+        self.layers[layer].matrix[coordinate.1][coordinate.0]
+    }
+
+    /// Given a coordinate, returns what is drawn there: the "pixel"
+    /// of the top-most layer with one on at the coordinate
+    pub fn visible(&self, coordinate: &Coordinate) -> PixelState {
+        self.draw_order
+            .iter()
+            .map(|&layer| self.pixel_on(layer, coordinate))
+            .find(|state| *state != PixelState::Off)
+            .unwrap_or(PixelState::Off)
+    }
+
+    /// Sets the state, and owner, of a "pixel" on a layer, recording it as dirty if it changed
+    fn set(&mut self, layer: LayerId, coordinate: Coordinate, state: PixelState, owner: EntityId) {
+        let (x, y) = coordinate;
+        self.layers[layer].owners[y][x] = owner;
+        if self.layers[layer].matrix[y][x] == state {
+            return;
+        }
+        self.layers[layer].matrix[y][x] = state;
+        if !self.is_dirty[y][x] {
+            self.is_dirty[y][x] = true;
+            self.dirty.push(coordinate);
+        }
+    }
+
+    /// Returns every "pixel" that changed since the last call,
+    /// leaving the dirty list empty
+    pub fn take_dirty(&mut self) -> Vec<Coordinate> {
+        for (x, y) in self.dirty.iter() {
+            self.is_dirty[*y][*x] = false;
+        }
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Forgets about the changed "pixels", used after the whole
+    /// plane has been redrawn
+    pub fn clear_dirty(&mut self) {
+        let _ = self.take_dirty();
     }
 
     /// Given a coordinate, this functions sets the state
     /// of a "pixel" to being on.
     pub fn spawn(&mut self, coordinate: Coordinate) {
-        self.matrix[coordinate.1][coordinate.0] = PixelState::On;
+        self.spawn_owned(
+            Self::PLANE,
+            coordinate,
+            Glyph::default(),
+            EntityId::ANONYMOUS,
+        );
     }
 
-    /// Swaps the pixel value of two coordinates
+    /// Given a layer and a coordinate, this functions sets the state of a
+    /// "pixel" to being on, drawn with the given glyph and owned by the entity.
+    pub fn spawn_owned(
+        &mut self,
+        layer: LayerId,
+        coordinate: Coordinate,
+        glyph: Glyph,
+        owner: EntityId,
+    ) {
+        self.set(layer, coordinate, PixelState::On(glyph), owner);
+    }
+
+    /// Swaps the pixel value, and owner, of two coordinates on the plane
     pub fn swap(&mut self, c1: Coordinate, c2: Coordinate) {
-        let tmp = self.matrix[c1.1][c1.0];
-        self.matrix[c1.1][c1.0] = self.matrix[c2.1][c2.0];
-        self.matrix[c2.1][c2.0] = tmp;
+        let owners = &self.layers[Self::PLANE].owners;
+        let (o1, o2) = (owners[c1.1][c1.0], owners[c2.1][c2.0]);
+        let tmp = self.pixel(&c1);
+        self.set(Self::PLANE, c1, self.pixel(&c2), o2);
+        self.set(Self::PLANE, c2, tmp, o1);
     }
 
     /// Returns a visual representation of the underlying matrix
+    ///
+    /// `pixel_char` is only used for "pixels" that weren't given a glyph
     pub fn display(&self, pixel_char: char, background_char: char) -> String {
         let mut interface = String::new();
         for r in 0..self.height {
             for c in 0..self.width {
-                match self.matrix[r][c] {
-                    PixelState::On => interface.push(pixel_char),
+                match self.visible(&(c, r)) {
+                    PixelState::On(glyph) => interface.push(glyph.ch.unwrap_or(pixel_char)),
                     PixelState::Off => interface.push(background_char),
                 }
             }
@@ -134,21 +377,93 @@ impl Engine {
         interface
     }
 
+    /// Returns a visual representation of the underlying matrix, where every
+    /// "pixel" is painted with the colors of its glyph through ANSI sequences
+    pub fn display_colored(&self, pixel_char: char, background_char: char) -> String {
+        let mut interface = String::new();
+        for r in 0..self.height {
+            // only emitting a color sequence when the color changes
+            let mut colors: (Option<Color>, Option<Color>) = (None, None);
+            for c in 0..self.width {
+                let (ch, fg, bg) = match self.visible(&(c, r)) {
+                    PixelState::On(glyph) => (glyph.ch.unwrap_or(pixel_char), glyph.fg, glyph.bg),
+                    PixelState::Off => (background_char, None, None),
+                };
+                if (fg, bg) != colors {
+                    interface += &ResetColor.to_string();
+                    if let Some(fg) = fg {
+                        interface += &SetForegroundColor(fg).to_string();
+                    }
+                    if let Some(bg) = bg {
+                        interface += &SetBackgroundColor(bg).to_string();
+                    }
+                    colors = (fg, bg);
+                }
+                interface.push(ch);
+            }
+            if colors != (None, None) {
+                interface += &ResetColor.to_string();
+            }
+            interface += "\n";
+        }
+        interface
+    }
+
     /// Given a coordinate, sets the state of a
-    /// "pixel" to being off.
+    /// "pixel" on the plane to being off.
     pub fn reset(&mut self, pixel: &Coordinate) {
+        self.set(Self::PLANE, *pixel, PixelState::Off, EntityId::ANONYMOUS);
+    }
+
+    /// Given a layer, a coordinate and an owner, sets the state of a
+    /// "pixel" to being off, only if the owner is still the one drawn there.
+    pub fn reset_on(&mut self, layer: LayerId, pixel: &Coordinate, owner: EntityId) {
         let (x, y) = *pixel;
-        self.matrix[y][x] = PixelState::Off;
+        if self.layers[layer].owners[y][x] == owner {
+            self.set(layer, *pixel, PixelState::Off, EntityId::ANONYMOUS);
+        }
     }
 
-    /// All "pixels" states are set to off
+    /// All "pixels" states, on every layer, are set to off
     pub fn clear(&mut self) {
-        self.matrix = (0..self.height)
-            .map(|_| {
-                (0..self.width)
-                    .map(|_| PixelState::Off)
-                    .collect::<Vec<PixelState>>()
-            })
-            .collect();
+        for layer in 0..self.layers.len() {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    self.set(layer, (x, y), PixelState::Off, EntityId::ANONYMOUS);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reset_on_keeps_pixels_drawn_over_by_another_owner() {
+        let mut engine = Engine::new((4, 4));
+        let overlay = engine.add_layer(1, false);
+        let (a, b) = (engine.new_entity(), engine.new_entity());
+        engine.spawn_owned(overlay, (1, 1), Glyph::new('a'), a);
+        engine.spawn_owned(overlay, (1, 1), Glyph::new('b'), b);
+        engine.reset_on(overlay, &(1, 1), a);
+        assert_eq!(
+            engine.pixel_on(overlay, &(1, 1)),
+            PixelState::On(Glyph::new('b'))
+        );
+        engine.reset_on(overlay, &(1, 1), b);
+        assert_eq!(engine.pixel_on(overlay, &(1, 1)), PixelState::Off);
+    }
+
+    #[test]
+    fn nothing_blocks_with_collisions_off() {
+        let mut engine = Engine::new((4, 4));
+        let owner = engine.new_entity();
+        engine.spawn_owned(Engine::PLANE, (2, 2), Glyph::new('#'), owner);
+        assert_eq!(engine.blocker(&(2, 2)), Some(owner));
+        let engine = engine.set_collisions(false);
+        assert_eq!(engine.blocker(&(2, 2)), None);
+        assert!(!engine.is_blocked(&(2, 2)));
     }
 }
@@ -1,7 +1,13 @@
 /// Abstracting import locations
 pub mod bounding_box;
+pub mod clock;
 mod engine;
+pub mod renderer;
 pub mod sprite;
 
 pub use engine::Coordinate;
 pub use engine::Engine;
+pub use engine::EntityId;
+pub use engine::Glyph;
+pub use engine::LayerId;
+pub use engine::PixelState;
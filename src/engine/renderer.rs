@@ -0,0 +1,442 @@
+//! Render Targets for the ASCII Engine
+//!
+//! This module decouples *what* gets drawn from *where* it gets drawn. The game
+//! hands every frame, a handful of status lines followed by the engine plane, to a
+//! [`Renderer`], which decides how that frame reaches the user.
+//!
+//! # Backends
+//! - [`TerminalRenderer`]: Draws the frame on the terminal. Only the "pixels" the
+//!   engine reports as dirty and the status lines that changed are rewritten, all in
+//!   a single buffered write per frame. The whole screen is redrawn on the first
+//!   frame, when the terminal is resized or when asked to. Pixels are painted with
+//!   their glyph's colors unless `NO_COLOR` is set or the terminal can't show them.
+//!   If the terminal is too small for the frame, the player is asked to enlarge it
+//!   instead of drawing wrapped, corrupted lines.
+//! - [`HeadlessRenderer`]: Never touches the terminal. Every presented frame is
+//!   recorded as a monochrome [`Frame`] snapshot so it can be inspected or compared
+//!   against a golden frame, making it possible to drive the game without a TTY
+//!   (e.g. on CI).
+//!
+//! # Example
+//! ```rust
+//! use crate::engine::renderer::{HeadlessRenderer, Renderer};
+//! use crate::engine::Engine;
+//!
+//! let mut engine = Engine::new((4, 2));
+//! engine.spawn((1, 0));
+//! let mut renderer = HeadlessRenderer::new('#', '.');
+//! renderer.present(&mut engine, &["status".to_string()]);
+//! assert_eq!(renderer.last_frame().unwrap().to_string(), "status\n.#..\n....\n");
+//! ```
+use crate::engine::{Coordinate, Engine, PixelState};
+use crate::utils;
+
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType};
+
+use std::fmt;
+use std::io::{self, stdout, Write};
+
+/// A surface capable of showing the engine plane
+pub trait Renderer: fmt::Debug {
+    /// Prepares the surface before the first frame is presented
+    fn clear(&mut self);
+
+    /// Returns false if a frame of the given size can't be shown whole
+    fn fits(&self, _width: usize, _height: usize) -> bool {
+        true
+    }
+
+    /// Presents a frame made out of the status lines followed by the plane,
+    /// consuming the engine's dirty "pixels"
+    fn present(&mut self, engine: &mut Engine, status: &[String]);
+
+    /// Presents a page of text in place of the plane, e.g. the high scores.
+    /// The next frame presented is drawn from scratch
+    fn present_page(&mut self, lines: &[String]);
+}
+
+/// A snapshot of a single presented frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// lines shown above the plane
+    pub status: Vec<String>,
+    /// one string per row of the plane
+    pub rows: Vec<String>,
+}
+
+impl Frame {
+    /// Returns the character drawn at the given plane coordinate
+    pub fn cell(&self, x: usize, y: usize) -> Option<char> {
+        self.rows.get(y)?.chars().nth(x)
+    }
+
+    /// Returns the plane as a grid of characters
+    pub fn grid(&self) -> Vec<Vec<char>> {
+        self.rows.iter().map(|row| row.chars().collect()).collect()
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in self.status.iter().chain(self.rows.iter()) {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws frames on the terminal, rewriting only what changed
+#[derive(Debug, Clone)]
+pub struct TerminalRenderer {
+    pixel_char: char,
+    background_char: char,
+    /// if false, glyph colors are ignored
+    colors: bool,
+    /// if true, the next frame is drawn from scratch
+    full_redraw: bool,
+    /// status lines currently on screen
+    drawn_status: Vec<String>,
+    /// terminal size when the last frame was drawn
+    terminal_size: Option<(u16, u16)>,
+    /// if true, the too small notice is on screen instead of the frame
+    too_small: bool,
+    /// page currently on screen instead of the plane, if any
+    drawn_page: Option<Vec<String>>,
+}
+
+impl TerminalRenderer {
+    /// Initializer function, colors are enabled when the terminal supports them
+    pub fn new(pixel_char: char, background_char: char) -> Self {
+        Self {
+            pixel_char,
+            background_char,
+            colors: utils::supports_color(),
+            full_redraw: true,
+            drawn_status: Vec::new(),
+            terminal_size: None,
+            too_small: false,
+            drawn_page: None,
+        }
+    }
+
+    /// Queues a notice asking for a bigger terminal in place of the frame
+    fn queue_too_small<W: Write>(
+        &mut self,
+        out: &mut W,
+        engine: &Engine,
+        status: &[String],
+    ) -> io::Result<()> {
+        let (columns, _) = self.terminal_size.unwrap_or((0, 0));
+        let notice = format!(
+            "The terminal is too small, please enlarge it to at least {}x{}",
+            engine.width,
+            engine.height + status.len()
+        );
+        queue!(out, ResetColor, Clear(ClearType::All))?;
+        // wrapping by hand, every chunk on its own row
+        let chars: Vec<char> = notice.chars().collect();
+        for (row, chunk) in chars.chunks((columns as usize).max(1)).enumerate() {
+            let line: String = chunk.iter().collect();
+            queue!(out, MoveTo(0, row as u16), Print(line))?;
+        }
+        Ok(())
+    }
+
+    /// Queues the whole frame, wiping whatever was on screen
+    fn queue_everything<W: Write>(
+        &mut self,
+        out: &mut W,
+        engine: &mut Engine,
+        status: &[String],
+    ) -> io::Result<()> {
+        queue!(out, ResetColor, Clear(ClearType::All))?;
+        for (row, line) in status.iter().enumerate() {
+            queue!(out, MoveTo(0, row as u16), Print(line))?;
+        }
+        let plane = match self.colors {
+            true => engine.display_colored(self.pixel_char, self.background_char),
+            false => engine.display(self.pixel_char, self.background_char),
+        };
+        for (row, line) in plane.lines().enumerate() {
+            queue!(out, MoveTo(0, (status.len() + row) as u16), Print(line))?;
+        }
+        engine.clear_dirty();
+        Ok(())
+    }
+
+    /// Queues only the status lines and "pixels" that changed since the last frame
+    fn queue_changes<W: Write>(
+        &mut self,
+        out: &mut W,
+        engine: &mut Engine,
+        status: &[String],
+    ) -> io::Result<()> {
+        for (row, line) in status.iter().enumerate() {
+            if self.drawn_status.get(row) != Some(line) {
+                queue!(
+                    out,
+                    MoveTo(0, row as u16),
+                    Print(line),
+                    Clear(ClearType::UntilNewLine)
+                )?;
+            }
+        }
+        let mut dirty: Vec<Coordinate> = engine.take_dirty();
+        // drawing row by row so consecutive "pixels" don't need a cursor move
+        dirty.sort_by_key(|&(x, y)| (y, x));
+        let mut cursor: Option<Coordinate> = None;
+        let mut colors: (Option<Color>, Option<Color>) = (None, None);
+        for (x, y) in dirty {
+            let (ch, fg, bg) = match engine.visible(&(x, y)) {
+                PixelState::On(glyph) => (glyph.ch.unwrap_or(self.pixel_char), glyph.fg, glyph.bg),
+                PixelState::Off => (self.background_char, None, None),
+            };
+            if cursor != Some((x, y)) {
+                queue!(out, MoveTo(x as u16, (status.len() + y) as u16))?;
+            }
+            if self.colors && (fg, bg) != colors {
+                queue!(out, ResetColor)?;
+                if let Some(fg) = fg {
+                    queue!(out, SetForegroundColor(fg))?;
+                }
+                if let Some(bg) = bg {
+                    queue!(out, SetBackgroundColor(bg))?;
+                }
+                colors = (fg, bg);
+            }
+            queue!(out, Print(ch))?;
+            cursor = Some((x + 1, y));
+        }
+        if colors != (None, None) {
+            queue!(out, ResetColor)?;
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn clear(&mut self) {
+        utils::clear();
+        self.full_redraw = true;
+    }
+
+    fn fits(&self, width: usize, height: usize) -> bool {
+        match terminal::size() {
+            Ok((columns, rows)) => width <= columns as usize && height <= rows as usize,
+            // not a terminal, nothing to fit in
+            Err(_) => true,
+        }
+    }
+
+    fn present(&mut self, engine: &mut Engine, status: &[String]) {
+        let terminal_size = terminal::size().ok();
+        let resized = terminal_size != self.terminal_size;
+        if resized || status.len() != self.drawn_status.len() {
+            // resized, or the plane would be drawn in a different place
+            self.full_redraw = true;
+        }
+        self.terminal_size = terminal_size;
+        let mut buffer: Vec<u8> = Vec::new();
+        let result = if !self.fits(engine.width, engine.height + status.len()) {
+            if self.too_small && !resized {
+                // the notice is already on screen
+                return;
+            }
+            // the frame is drawn whole once the terminal is big enough again
+            self.too_small = true;
+            self.full_redraw = true;
+            self.queue_too_small(&mut buffer, engine, status)
+        } else if self.full_redraw {
+            self.too_small = false;
+            self.full_redraw = false;
+            self.queue_everything(&mut buffer, engine, status)
+        } else {
+            self.queue_changes(&mut buffer, engine, status)
+        };
+        result.expect("Error at queueing the frame");
+        queue!(buffer, MoveTo(0, 0)).expect("Error at moving");
+        let mut out = stdout().lock();
+        out.write_all(&buffer).expect("Error at writing to stdout");
+        out.flush().expect("Error at flush to stdout");
+        self.drawn_status = status.to_vec();
+        self.drawn_page = None;
+    }
+
+    fn present_page(&mut self, lines: &[String]) {
+        let terminal_size = terminal::size().ok();
+        if self.drawn_page.as_deref() == Some(lines) && terminal_size == self.terminal_size {
+            // the page is already on screen
+            return;
+        }
+        self.terminal_size = terminal_size;
+        let mut buffer: Vec<u8> = Vec::new();
+        queue!(buffer, ResetColor, Clear(ClearType::All)).expect("Error at clearing");
+        for (row, line) in lines.iter().enumerate() {
+            queue!(buffer, MoveTo(0, row as u16), Print(line)).expect("Error at queueing the page");
+        }
+        queue!(buffer, MoveTo(0, 0)).expect("Error at moving");
+        let mut out = stdout().lock();
+        out.write_all(&buffer).expect("Error at writing to stdout");
+        out.flush().expect("Error at flush to stdout");
+        self.drawn_page = Some(lines.to_vec());
+        self.full_redraw = true;
+    }
+}
+
+/// Records every frame in memory instead of drawing it
+#[derive(Debug, Clone)]
+pub struct HeadlessRenderer {
+    pixel_char: char,
+    background_char: char,
+    frames: Vec<Frame>,
+}
+
+impl HeadlessRenderer {
+    pub fn new(pixel_char: char, background_char: char) -> Self {
+        Self {
+            pixel_char,
+            background_char,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Returns every frame presented so far, oldest first
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Returns the most recently presented frame
+    pub fn last_frame(&self) -> Option<&Frame> {
+        self.frames.last()
+    }
+
+    /// Hands over the recorded frames, leaving the recorder empty
+    pub fn take_frames(&mut self) -> Vec<Frame> {
+        std::mem::take(&mut self.frames)
+    }
+}
+
+impl Renderer for HeadlessRenderer {
+    fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    fn present(&mut self, engine: &mut Engine, status: &[String]) {
+        engine.clear_dirty();
+        let rows: Vec<String> = engine
+            .display(self.pixel_char, self.background_char)
+            .lines()
+            .map(|line| line.to_string())
+            .collect();
+        self.frames.push(Frame {
+            status: status.to_vec(),
+            rows,
+        });
+    }
+
+    fn present_page(&mut self, lines: &[String]) {
+        // recorded as a frame without a plane
+        self.frames.push(Frame {
+            status: lines.to_vec(),
+            rows: Vec::new(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{EntityId, Glyph};
+
+    /// Returns a renderer that already drew the engine's plane under a status line
+    fn drawn(engine: &mut Engine, colors: bool) -> (TerminalRenderer, Vec<String>) {
+        let mut renderer = TerminalRenderer::new('#', '.');
+        renderer.colors = colors;
+        let status = vec!["SCORE: 0".to_string()];
+        let mut out: Vec<u8> = Vec::new();
+        renderer
+            .queue_everything(&mut out, engine, &status)
+            .unwrap();
+        renderer.drawn_status = status.clone();
+        (renderer, status)
+    }
+
+    fn changes(renderer: &mut TerminalRenderer, engine: &mut Engine, status: &[String]) -> String {
+        let mut out: Vec<u8> = Vec::new();
+        renderer.queue_changes(&mut out, engine, status).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Returns true if the output sets a foreground or background color
+    fn sets_colors(output: &str) -> bool {
+        output.contains("\x1B[38;") || output.contains("\x1B[48;")
+    }
+
+    #[test]
+    fn only_the_changed_cells_are_written() {
+        let mut engine = Engine::new((6, 3));
+        let (mut renderer, status) = drawn(&mut engine, false);
+        assert_eq!(changes(&mut renderer, &mut engine, &status), "");
+
+        engine.spawn((2, 1));
+        engine.spawn((3, 1));
+        engine.spawn((5, 2));
+        let mut expected: Vec<u8> = Vec::new();
+        // the two side by side share a single cursor move
+        queue!(expected, MoveTo(2, 2), Print('#'), Print('#')).unwrap();
+        queue!(expected, MoveTo(5, 3), Print('#')).unwrap();
+        let expected = String::from_utf8(expected).unwrap();
+        assert_eq!(changes(&mut renderer, &mut engine, &status), expected);
+        assert_eq!(changes(&mut renderer, &mut engine, &status), "");
+    }
+
+    #[test]
+    fn only_the_changed_status_lines_are_written() {
+        let mut engine = Engine::new((6, 3));
+        let (mut renderer, _) = drawn(&mut engine, false);
+        let status = vec!["SCORE: 10".to_string()];
+        let mut expected: Vec<u8> = Vec::new();
+        queue!(
+            expected,
+            MoveTo(0, 0),
+            Print("SCORE: 10"),
+            Clear(ClearType::UntilNewLine)
+        )
+        .unwrap();
+        let expected = String::from_utf8(expected).unwrap();
+        assert_eq!(changes(&mut renderer, &mut engine, &status), expected);
+    }
+
+    #[test]
+    fn no_colors_are_set_when_they_are_disabled() {
+        let red = Glyph::new('x').fg(Color::Red).bg(Color::Black);
+        let mut engine = Engine::new((6, 3));
+        engine.spawn_owned(Engine::PLANE, (1, 1), red, EntityId::ANONYMOUS);
+        let mut out: Vec<u8> = Vec::new();
+        let (mut renderer, status) = drawn(&mut engine, false);
+        renderer
+            .queue_everything(&mut out, &mut engine, &status)
+            .unwrap();
+        assert!(!sets_colors(&String::from_utf8(out).unwrap()));
+
+        engine.spawn_owned(Engine::PLANE, (4, 2), red, EntityId::ANONYMOUS);
+        let output = changes(&mut renderer, &mut engine, &status);
+        assert!(output.contains('x'));
+        assert!(!sets_colors(&output));
+    }
+
+    #[test]
+    fn glyph_colors_are_set_when_enabled() {
+        let red = Glyph::new('x').fg(Color::Red);
+        let mut engine = Engine::new((6, 3));
+        let (mut renderer, status) = drawn(&mut engine, true);
+        engine.spawn_owned(Engine::PLANE, (4, 2), red, EntityId::ANONYMOUS);
+        let output = changes(&mut renderer, &mut engine, &status);
+        assert!(sets_colors(&output));
+        // leaving the terminal colors as they were found
+        assert!(output.ends_with(&ResetColor.to_string()));
+    }
+}
@@ -16,6 +16,9 @@
 //! - Axis-aligned bounding box tracking for collision and bounds enforcement
 //! - Velocity-based movement with time delta input
 //! - Coordinate-based removal and runtime updates
+//! - A glyph, character and colors, drawn for every pixel of the sprite
+//! - A layer of the engine to be drawn on, colliding with the other sprites or not
+//! - An [`EntityId`] owning its pixels, reported to whichever sprite runs into them
 //!
 //! # Sprite Lifecycle
 //! A sprite is constructed with a reference to an `Engine`, a list of coordinates,
@@ -41,12 +44,16 @@
 //! Every major operation returns a [`State`] enum that indicates the effect of
 //! that operation. This includes outcomes such as:
 //! - `State::Moved`
-//! - `State::Collided((x, y))`
+//! - `State::Collided { coordinate: (x, y), other }`, `other` being the
+//!   [`EntityId`] of the sprite run into
 //! - `State::Destroyed`
 //! - `State::Null`
 use crate::engine::bounding_box::BoundingBox;
 use crate::engine::Coordinate;
 use crate::engine::Engine;
+use crate::engine::EntityId;
+use crate::engine::Glyph;
+use crate::engine::LayerId;
 use crate::errors::{Error, ErrorKind};
 
 use std::cell::RefCell;
@@ -56,7 +63,10 @@ use std::sync::{Arc, RwLock};
 /// State of the sprite after a method has been called
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum State {
-    Collided(Coordinate),
+    Collided {
+        coordinate: Coordinate,
+        other: EntityId,
+    },
     Destroyed,
     Spawned,
     Moved,
@@ -64,6 +74,14 @@ pub enum State {
     Null,
 }
 
+/// What an entity ran into while stepping,
+/// the coordinate it hit and the entity owning it
+#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+pub struct Collision {
+    pub coordinate: Coordinate,
+    pub other: EntityId,
+}
+
 /// Controls a group of pixels in the engine
 #[derive(Debug, Clone)]
 pub struct Sprite {
@@ -78,6 +96,12 @@ pub struct Sprite {
     fx: f32,
     /// delta for vertical change
     fy: f32,
+    /// how every pixel of the sprite is drawn
+    glyph: Glyph,
+    /// layer of the engine the sprite is drawn on
+    layer: LayerId,
+    /// owner of the sprite's pixels in the engine
+    id: EntityId,
 }
 
 impl Sprite {
@@ -117,6 +141,7 @@ impl Sprite {
             }
         }
         let bounding_box = BoundingBox::from(&coordinates);
+        let id = engine.borrow_mut().new_entity();
         Ok(Self {
             engine,
             x_velocity,
@@ -127,9 +152,47 @@ impl Sprite {
             is_destroyed: false,
             fx: 0.0,
             fy: 0.0,
+            glyph: Glyph::default(),
+            layer: Engine::PLANE,
+            id,
         })
     }
 
+    /// Builder function setting the glyph the sprite is drawn with
+    pub fn with_glyph(mut self, glyph: Glyph) -> Self {
+        self.glyph = glyph;
+        self
+    }
+
+    /// Builder function setting the layer of the engine the sprite is drawn on,
+    /// it's only run into by other sprites, and runs into them, if the layer collides
+    pub fn with_layer(mut self, layer: LayerId) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Returns the id owning the sprite's pixels in the engine
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
+    /// Sets the glyph the sprite is drawn with,
+    /// redrawing the sprite if it is already spawned
+    pub fn set_glyph(&mut self, glyph: Glyph) {
+        self.glyph = glyph;
+        if self.is_spawned {
+            let mut engine = self.engine.borrow_mut();
+            for coordinate in self.coordinates.iter() {
+                engine.spawn_owned(self.layer, *coordinate, self.glyph, self.id);
+            }
+        }
+    }
+
+    /// Returns the glyph the sprite is drawn with
+    pub fn glyph(&self) -> Glyph {
+        self.glyph
+    }
+
     /// Returns a clone of the underlying engine
     pub fn engine(&self) -> Rc<RefCell<Engine>> {
         self.engine.clone()
@@ -245,7 +308,7 @@ impl Sprite {
         }
         for c in 0..self.coordinates.len() {
             if self.coordinates[c] == coordinate {
-                engine.reset(&coordinate); // set to off
+                engine.reset_on(self.layer, &coordinate, self.id); // set to off
                 let _ = self.coordinates.remove(c);
                 return Ok(State::Hit);
             }
@@ -258,7 +321,8 @@ impl Sprite {
     pub fn spawn(&mut self) -> Result<State, Error> {
         let mut eng = self.engine.borrow_mut();
         {
-            if self.coordinates.iter().any(|coor| eng.is_on(coor)) {
+            let collides = eng.collides(self.layer);
+            if collides && self.coordinates.iter().any(|coor| eng.is_blocked(coor)) {
                 return Err(Error::new(
                     ErrorKind::OverlappingSprite,
                     "A sprite already exists within given coordinates",
@@ -267,7 +331,7 @@ impl Sprite {
         }
         {
             for coordinate in self.coordinates.iter() {
-                eng.spawn(*coordinate); // sets to on
+                eng.spawn_owned(self.layer, *coordinate, self.glyph, self.id); // sets to on
             }
         }
         self.is_destroyed = false;
@@ -314,22 +378,25 @@ impl Sprite {
         {
             self.fy = 0.0 // reseting vertical delta
         }
-        if engine.collisions() {
+        if engine.collides(self.layer) {
             // collision detection
             //
             // looks into the future coordinate position and checks
             // if such coordinate is already on.
             for col in self.bounding_box.far_left..=self.bounding_box.far_right {
                 let future_coordinate = (col, self.bounding_box.far_top - step);
-                if engine.is_on(&future_coordinate) {
-                    return Ok(State::Collided(future_coordinate));
+                if let Some(other) = engine.blocker(&future_coordinate) {
+                    return Ok(State::Collided {
+                        coordinate: future_coordinate,
+                        other,
+                    });
                 }
             }
         }
         if self.is_spawned {
             // reseting the current position
             for coordinate in self.coordinates.iter() {
-                engine.reset(coordinate);
+                engine.reset_on(self.layer, coordinate, self.id);
             }
         }
         {
@@ -337,7 +404,7 @@ impl Sprite {
             for coordinate in self.coordinates.iter_mut() {
                 let new = (coordinate.0, coordinate.1 - step);
                 if self.is_spawned {
-                    engine.spawn(new);
+                    engine.spawn_owned(self.layer, new, self.glyph, self.id);
                 }
                 *coordinate = new;
             }
@@ -386,22 +453,25 @@ impl Sprite {
         {
             self.fx = 0.0 // reseting horizontal delta
         }
-        if engine.collisions() {
+        if engine.collides(self.layer) {
             // collision detection
             //
             // looks into the future coordinate position and checks
             // if such coordinate is already on.
             for row in self.bounding_box.far_top..=self.bounding_box.far_bottom {
                 let future_coordinate = (self.bounding_box.far_left - step, row);
-                if engine.is_on(&future_coordinate) {
-                    return Ok(State::Collided(future_coordinate));
+                if let Some(other) = engine.blocker(&future_coordinate) {
+                    return Ok(State::Collided {
+                        coordinate: future_coordinate,
+                        other,
+                    });
                 }
             }
         }
         if self.is_spawned {
             // reseting the current position
             for coordinate in self.coordinates.iter() {
-                engine.reset(coordinate);
+                engine.reset_on(self.layer, coordinate, self.id);
             }
         }
         {
@@ -409,7 +479,7 @@ impl Sprite {
             for coordinate in self.coordinates.iter_mut() {
                 let new = (coordinate.0 - step, coordinate.1);
                 if self.is_spawned {
-                    engine.spawn(new);
+                    engine.spawn_owned(self.layer, new, self.glyph, self.id);
                 }
                 *coordinate = new;
             }
@@ -458,19 +528,22 @@ impl Sprite {
         {
             self.fx = 0.0; // reseting horizontal delta
         }
-        if engine.collisions() {
+        if engine.collides(self.layer) {
             // collision detection; looking into the future step if it is populated
             for row in self.bounding_box.far_top..=self.bounding_box.far_bottom {
                 let future_coordinate = (self.bounding_box.far_right + step, row);
-                if engine.is_on(&future_coordinate) {
-                    return Ok(State::Collided(future_coordinate));
+                if let Some(other) = engine.blocker(&future_coordinate) {
+                    return Ok(State::Collided {
+                        coordinate: future_coordinate,
+                        other,
+                    });
                 }
             }
         }
         if self.is_spawned {
             // reseting the current position
             for coordinate in self.coordinates.iter() {
-                engine.reset(coordinate);
+                engine.reset_on(self.layer, coordinate, self.id);
             }
         }
         {
@@ -478,7 +551,7 @@ impl Sprite {
             for coordinate in self.coordinates.iter_mut() {
                 let new = (coordinate.0 + step, coordinate.1);
                 if self.is_spawned {
-                    engine.spawn(new);
+                    engine.spawn_owned(self.layer, new, self.glyph, self.id);
                 }
                 *coordinate = new;
             }
@@ -523,19 +596,22 @@ impl Sprite {
         {
             self.fy = 0.0 // reseting vertical delta
         }
-        if engine.collisions() {
+        if engine.collides(self.layer) {
             // collision detection
             for col in self.bounding_box.far_left..=self.bounding_box.far_right {
                 let future_coordinate = (col, self.bounding_box.far_bottom + step);
-                if engine.is_on(&future_coordinate) {
-                    return Ok(State::Collided(future_coordinate));
+                if let Some(other) = engine.blocker(&future_coordinate) {
+                    return Ok(State::Collided {
+                        coordinate: future_coordinate,
+                        other,
+                    });
                 }
             }
         }
         if self.is_spawned {
             // reseting the current position
             for coordinate in self.coordinates.iter() {
-                engine.reset(coordinate);
+                engine.reset_on(self.layer, coordinate, self.id);
             }
         }
         {
@@ -543,7 +619,7 @@ impl Sprite {
             for coordinate in self.coordinates.iter_mut() {
                 let new = (coordinate.0, coordinate.1 + step);
                 if self.is_spawned {
-                    engine.spawn(new);
+                    engine.spawn_owned(self.layer, new, self.glyph, self.id);
                 }
                 *coordinate = new;
             }
@@ -580,15 +656,18 @@ impl Sprite {
                 ));
             }
         }
-        if engine.collisions() {
+        if engine.collides(self.layer) {
             // checking for collisions
             if step > 0 {
                 // positive step, moving right
                 for col in self.bounding_box.far_left..=self.bounding_box.far_right {
                     let future_coordinate: Coordinate =
                         (col, (self.bounding_box.far_bottom as i32 + step) as usize);
-                    if engine.is_on(&future_coordinate) {
-                        return Ok(State::Collided(future_coordinate));
+                    if let Some(other) = engine.blocker(&future_coordinate) {
+                        return Ok(State::Collided {
+                            coordinate: future_coordinate,
+                            other,
+                        });
                     }
                 }
             } else {
@@ -596,8 +675,11 @@ impl Sprite {
                 for col in self.bounding_box.far_left..=self.bounding_box.far_right {
                     let future_coordinate: Coordinate =
                         (col, (self.bounding_box.far_top as i32 + step) as usize);
-                    if engine.is_on(&future_coordinate) {
-                        return Ok(State::Collided(future_coordinate));
+                    if let Some(other) = engine.blocker(&future_coordinate) {
+                        return Ok(State::Collided {
+                            coordinate: future_coordinate,
+                            other,
+                        });
                     }
                 }
             }
@@ -605,7 +687,7 @@ impl Sprite {
         if self.is_spawned {
             // reseting the current position
             for coordinate in self.coordinates.iter() {
-                engine.reset(coordinate);
+                engine.reset_on(self.layer, coordinate, self.id);
             }
         }
         {
@@ -613,7 +695,7 @@ impl Sprite {
             for coordinate in self.coordinates.iter_mut() {
                 let new = (coordinate.0, (coordinate.1 as i32 + step) as usize);
                 if self.is_spawned {
-                    engine.spawn(new);
+                    engine.spawn_owned(self.layer, new, self.glyph, self.id);
                 }
                 *coordinate = new;
             }
@@ -657,15 +739,18 @@ impl Sprite {
                 ));
             }
         }
-        if engine.collisions() {
+        if engine.collides(self.layer) {
             // checking for collisions
             if step > 0 {
                 // positive step, moving right
                 for row in self.bounding_box.far_top..=self.bounding_box.far_bottom {
                     let future_coordinate: Coordinate =
                         (self.bounding_box.far_right + step as usize, row);
-                    if engine.is_on(&future_coordinate) {
-                        return Ok(State::Collided(future_coordinate));
+                    if let Some(other) = engine.blocker(&future_coordinate) {
+                        return Ok(State::Collided {
+                            coordinate: future_coordinate,
+                            other,
+                        });
                     }
                 }
             } else {
@@ -673,8 +758,11 @@ impl Sprite {
                 for row in self.bounding_box.far_top..=self.bounding_box.far_bottom {
                     let future_coordinate: Coordinate =
                         ((self.bounding_box.far_left as i32 + step) as usize, row);
-                    if engine.is_on(&future_coordinate) {
-                        return Ok(State::Collided(future_coordinate));
+                    if let Some(other) = engine.blocker(&future_coordinate) {
+                        return Ok(State::Collided {
+                            coordinate: future_coordinate,
+                            other,
+                        });
                     }
                 }
             }
@@ -682,7 +770,7 @@ impl Sprite {
         if self.is_spawned {
             // reseting the current position
             for coordinate in self.coordinates.iter() {
-                engine.reset(coordinate);
+                engine.reset_on(self.layer, coordinate, self.id);
             }
         }
         {
@@ -690,7 +778,7 @@ impl Sprite {
             for coordinate in self.coordinates.iter_mut() {
                 let new = ((coordinate.0 as i32 + step) as usize, coordinate.1);
                 if self.is_spawned {
-                    engine.spawn(new);
+                    engine.spawn_owned(self.layer, new, self.glyph, self.id);
                 }
                 *coordinate = new;
             }
@@ -708,7 +796,7 @@ impl Sprite {
     pub fn destroy(&mut self) -> State {
         let mut engine = self.engine.borrow_mut();
         for coor in self.coordinates.iter() {
-            engine.reset(coor);
+            engine.reset_on(self.layer, coor, self.id);
         }
         self.is_destroyed = true;
         self.is_spawned = false;
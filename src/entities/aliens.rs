@@ -1,15 +1,25 @@
 //! aliens.rs
 //! Contains logic and structures for managing alien invaders.
+//!
+//! Besides marching with the formation, an alien can be armored, losing a
+//! pixel per hit until its hit points run out, dive at the shooter or split
+//! in two when destroyed, as its type in the formations says.
+use crate::engine::clock::{Clock, Timer};
+use crate::engine::sprite::Collision;
 use crate::engine::sprite::Sprite;
 use crate::engine::sprite::State;
 use crate::engine::Coordinate;
 use crate::engine::Engine;
+use crate::engine::EntityId;
 use crate::errors::{Error, ErrorKind};
+use crate::formation::{self, Behaviour};
 use crate::utils;
+use crate::wave::Wave;
 
-use crate::ALIEN_STEP_PER_DELTA;
+use crate::PALETTE;
 
 use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::rc::Rc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -36,6 +46,13 @@ pub struct Alien {
     sprite: Sprite,
     velocity: f32,
     width: usize,
+    /// row of the formation the alien was spawned in, 0 being the top one
+    row: usize,
+    /// hits left before the alien is destroyed
+    hit_points: usize,
+    behaviour: Behaviour,
+    /// started when the alien last fired, `None` if it never did
+    reload_timer: Option<Timer>,
 }
 
 impl Alien {
@@ -48,10 +65,112 @@ impl Alien {
         Ok(Self {
             width,
             velocity,
-            sprite: Sprite::new(engine, position, velocity, velocity)?,
+            row: 0,
+            hit_points: 1,
+            behaviour: Behaviour::March,
+            reload_timer: None,
+            sprite: Sprite::new(engine, position, velocity, velocity)?.with_glyph(PALETTE.alien),
         })
     }
 
+    /// Setter function for the formation row the alien belongs to
+    pub fn with_row(mut self, row: usize) -> Self {
+        self.row = row;
+        self
+    }
+
+    /// Returns the formation row the alien was spawned in
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Setter function for the hits the alien takes to be destroyed
+    pub fn with_hit_points(mut self, hit_points: usize) -> Self {
+        self.hit_points = hit_points.max(1);
+        self
+    }
+
+    /// Setter function for what the alien does besides marching
+    pub fn with_behaviour(mut self, behaviour: Behaviour) -> Self {
+        self.behaviour = behaviour;
+        self
+    }
+
+    /// Returns what the alien does besides marching
+    pub fn behaviour(&self) -> &Behaviour {
+        &self.behaviour
+    }
+
+    /// Takes a hit point from the alien at the given coordinate, destroying
+    /// it once there are none left. Returns true if it was destroyed
+    pub fn hit(&mut self, coordinate: Coordinate) -> bool {
+        self.hit_points = self.hit_points.saturating_sub(1);
+        if self.hit_points == 0 {
+            self.destroy();
+            return true;
+        }
+        // armor, knocking the pixel that was hit off. The last
+        // one is kept, a sprite can't be without a body
+        if self.sprite.coordinates().len() > 1 && self.sprite.pop(coordinate).is_ok() {
+            self.sprite.recalc_bounding_box();
+        }
+        false
+    }
+
+    /// Returns true if the alien fired longer than `cooldown` seconds ago, or never did
+    pub fn is_loaded(&self, cooldown: f32, clock: &Clock) -> bool {
+        self.reload_timer
+            .is_none_or(|timer| timer.elapsed(clock) >= cooldown)
+    }
+
+    /// Records the alien firing, it has to cool down before firing again
+    pub fn reload(&mut self, clock: &Clock) {
+        self.reload_timer = Some(Timer::start(clock));
+    }
+
+    /// Gets the alien ready to break off from the formation and dive
+    pub fn start_dive(&mut self, velocity: f32) {
+        self.sprite.set_y_velocity(velocity);
+    }
+
+    /// Swoops down, veering a column towards the target column every row
+    ///
+    /// Returns an `OutOfBounds` error once the alien reached the bottom of the plane
+    pub fn dive(&mut self, target_x: usize, delta_time: f32) -> Result<State, Error> {
+        let top = self.sprite.far_top();
+        let state = self.sprite.move_down(delta_time)?;
+        if self.sprite.far_top() == top {
+            return Ok(state);
+        }
+        self.veer(target_x, state)
+    }
+
+    /// Carries on a dive that was stopped at the given coordinate, once
+    /// whatever was there is out of the way
+    pub fn resume_dive(&mut self, target_x: usize, stopped_at: Coordinate) -> Result<State, Error> {
+        let bottom = self.sprite.far_bottom();
+        if stopped_at.1 <= bottom {
+            // stopped veering, the row was already moved down
+            return self.veer(target_x, State::Null);
+        }
+        let state = self
+            .sprite
+            .move_relative_y((stopped_at.1 - bottom) as i32)?;
+        if let State::Collided { .. } = state {
+            return Ok(state);
+        }
+        self.veer(target_x, state)
+    }
+
+    /// Moves a column towards the target column, if not already on it
+    fn veer(&mut self, target_x: usize, state: State) -> Result<State, Error> {
+        match self.head().0.cmp(&target_x) {
+            Ordering::Less => self.sprite.move_relative_x(1),
+            Ordering::Greater => self.sprite.move_relative_x(-1),
+            Ordering::Equal => Ok(state),
+        }
+    }
+
     pub fn x(&self) -> usize {
         self.sprite.far_left()
     }
@@ -68,6 +187,10 @@ impl Alien {
         self.sprite.far_bottom()
     }
 
+    pub fn far_top(&self) -> usize {
+        self.sprite.far_top()
+    }
+
     pub fn spawn(&mut self) -> Result<(), Error> {
         let _ = self.sprite.spawn()?;
         Ok(())
@@ -78,14 +201,14 @@ impl Alien {
     }
 
     /// The update function for movement
-    pub fn step(&mut self, step: i32) -> Option<Coordinate> {
+    pub fn step(&mut self, step: i32) -> Option<Collision> {
         if self.sprite.is_destroyed() {
             return None;
         }
         return match self.sprite.move_relative_x(step) {
             Ok(state) => {
-                if let State::Collided(coordinate) = state {
-                    Some(coordinate)
+                if let State::Collided { coordinate, other } = state {
+                    Some(Collision { coordinate, other })
                 } else {
                     None
                 }
@@ -110,57 +233,117 @@ impl Alien {
         let _ = self.sprite.destroy();
     }
 
-    pub fn contains(&self, coordinate: Coordinate) -> bool {
-        self.sprite.contains(coordinate)
+    /// Returns the id owning its pixels in the engine
+    pub fn id(&self) -> EntityId {
+        self.sprite.id()
+    }
+}
+
+/// Returns the indices of the aliens with no other alien below them
+pub fn front_line(aliens: &[Alien]) -> Vec<usize> {
+    (0..aliens.len())
+        .filter(|&a| {
+            let alien = &aliens[a];
+            !aliens.iter().any(|other| {
+                other.far_top() > alien.far_bottom()
+                    && other.far_left() <= alien.far_right()
+                    && other.far_right() >= alien.far_left()
+            })
+        })
+        .collect()
+}
+
+/// What a hit did to an alien
+#[derive(Debug, Clone)]
+pub enum AlienHit {
+    /// the alien took the hit, but has hit points left
+    Damaged,
+    /// the alien was destroyed, handed back so it can be scored
+    Destroyed(Alien),
+}
+
+/// Hits the alien the collision was with, taking it out of the formation once destroyed
+pub fn find_alien_and_hit(aliens: &mut Vec<Alien>, collision: Collision) -> Option<AlienHit> {
+    let i = aliens
+        .iter()
+        .position(|alien| alien.id() == collision.other)?;
+    match aliens[i].hit(collision.coordinate) {
+        true => Some(AlienHit::Destroyed(aliens.remove(i))),
+        false => Some(AlienHit::Damaged),
     }
 }
 
-pub fn find_alien_and_destroy(aliens: &mut Vec<Alien>, coordinate: Coordinate) -> bool {
+/// Destroys the alien with the id, handing it back so it can be scored
+pub fn find_alien_and_destroy(aliens: &mut Vec<Alien>, id: EntityId) -> Option<Alien> {
     for i in 0..aliens.len() {
-        if aliens[i].contains(coordinate) {
+        if aliens[i].id() == id {
             aliens[i].destroy();
-            let _ = aliens.remove(i);
-            return true;
+            return Some(aliens.remove(i));
         }
     }
-    false
+    None
 }
 
-/// Iterates over the plane making the necessary calculations to spawn the `Alien` sprites
-pub fn spawn_aliens(
-    engine: Rc<RefCell<Engine>>,
-    count: usize,
-    velocity: f32,
-) -> Result<Vec<Alien>, Error> {
-    let eng = engine.borrow();
+/// Spawns the `Alien` sprites of a wave, laid out as the formations say
+pub fn spawn_aliens(engine: Rc<RefCell<Engine>>, wave: &Wave) -> Result<Vec<Alien>, Error> {
+    let plane = {
+        let eng = engine.borrow();
+        (eng.width, eng.height)
+    };
     let mut collector: Vec<Alien> = Vec::new();
-    let width = 4; // sprite width
-    let delta = eng.width / count;
-    for row in [4, 8, 12] {
-        for col in 0..eng.width {
-            if col % delta != 0 {
-                // this if statement automatically deals with even
-                // spacing. For example, if count was 6, for every 6th
-                // iteration, an `Alien` entity will be spawned
-                continue;
-            }
-            if col + width >= eng.width {
-                // this if statement makes sure to not go over the
-                // plane dimensions and cause an overflow
-                continue;
+    for placement in formation::get().place(wave, plane) {
+        collector.push(
+            Alien::new(
+                engine.clone(),
+                placement.position,
+                wave.alien_step_per_delta,
+            )?
+            .with_row(placement.row)
+            .with_hit_points(placement.alien_type.hit_points)
+            .with_behaviour(placement.alien_type.behaviour),
+        );
+    }
+    Ok(collector)
+}
+
+/// Spawns the two aliens a destroyed splitting alien breaks into, one on each
+/// of its sides. Those that don't fit where it was are left out
+pub fn split_alien(alien: &Alien, velocity: f32) -> Vec<Alien> {
+    let alien_type = match alien.behaviour() {
+        Behaviour::Split(name) => match formation::get().alien_type(name) {
+            Some(alien_type) => alien_type,
+            None => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+    let engine = alien.sprite.engine();
+    let plane = {
+        let eng = engine.borrow();
+        (eng.width, eng.height)
+    };
+    let right = (alien.far_right() + 1).saturating_sub(alien_type.width);
+    let mut halves = Vec::new();
+    for x in [alien.far_left(), right] {
+        if x + alien_type.width > plane.0 || alien.far_top() + alien_type.height > plane.1 {
+            continue;
+        }
+        let half = Alien::new(
+            engine.clone(),
+            alien_type.at((x, alien.far_top())),
+            velocity,
+        );
+        if let Ok(mut half) = half {
+            // the second half is left out if it overlaps the first
+            if half.spawn().is_ok() {
+                halves.push(
+                    half.with_row(alien.row)
+                        .with_hit_points(alien_type.hit_points)
+                        .with_behaviour(alien_type.behaviour.clone()),
+                );
             }
-            let position = vec![
-                (col, row),
-                (col + 1, row),
-                (col + 2, row),
-                (col, row + 1),
-                //(pointer + 1, row + 1), // not including it for the alien aesthetic
-                (col + 2, row + 1),
-            ];
-            collector.push(Alien::new(engine.clone(), position, velocity)?);
         }
     }
-    Ok(collector)
+    halves
 }
 
 /// returns a reference to the farthest right most alien
@@ -1,13 +1,15 @@
 //! Contains the logic for bullets such as
 //! a bullets interaction with another entity
+use crate::engine::sprite::Collision;
 use crate::engine::sprite::Sprite;
 use crate::engine::sprite::State;
 use crate::engine::Coordinate;
 use crate::engine::Engine;
+use crate::engine::EntityId;
 use crate::errors::{Error, ErrorKind};
 use crate::utils;
 use crate::SpaceInvaders;
-use crate::BULLET_STEP_PER_DELTA;
+use crate::PALETTE;
 
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -16,6 +18,8 @@ use std::rc::Rc;
 pub struct Bullet {
     sprite: Sprite,
     is_alien_bullet: bool,
+    /// if true, the bullet goes through the aliens it hits
+    is_piercing: bool,
 }
 
 impl Bullet {
@@ -25,8 +29,10 @@ impl Bullet {
         velocity: f32,
     ) -> Result<Self, Error> {
         Ok(Self {
-            sprite: Sprite::new(engine, vec![position], velocity, velocity)?,
+            sprite: Sprite::new(engine, vec![position], velocity, velocity)?
+                .with_glyph(PALETTE.bullet),
             is_alien_bullet: false,
+            is_piercing: false,
         })
     }
 
@@ -51,6 +57,7 @@ impl Bullet {
     /// will travel downwards towards the player
     pub fn to_alien_bullet(mut self) -> Self {
         self.is_alien_bullet = true;
+        self.sprite.set_glyph(PALETTE.alien_bullet);
         self
     }
 
@@ -58,21 +65,34 @@ impl Bullet {
         self.is_alien_bullet
     }
 
-    pub fn contains(&self, coordinate: Coordinate) -> bool {
-        self.sprite.contains(coordinate)
+    /// sets a bullet to pierce, meaning the bullet keeps
+    /// going after hitting an alien instead of being destroyed
+    pub fn to_piercing_bullet(mut self) -> Self {
+        self.is_piercing = true;
+        self.sprite.set_glyph(PALETTE.piercing_bullet);
+        self
+    }
+
+    pub fn is_piercing(&self) -> bool {
+        self.is_piercing
+    }
+
+    /// Returns the id owning its pixels in the engine
+    pub fn id(&self) -> EntityId {
+        self.sprite.id()
     }
 
     /// The update function for the entity
-    pub fn step(&mut self, delta_time: f32) -> Option<Coordinate> {
+    pub fn step(&mut self, delta_time: f32) -> Option<Collision> {
         let result = match self.is_alien_bullet {
             true => self.sprite.move_down(delta_time),
             false => self.sprite.move_up(delta_time),
         };
         return match result {
             Ok(state) => match state {
-                State::Collided(coordinate) => {
+                State::Collided { coordinate, other } => {
                     //self.sprite.destroy();
-                    Some(coordinate)
+                    Some(Collision { coordinate, other })
                 }
                 _ => None,
             },
@@ -0,0 +1,108 @@
+//! Defensive bunkers standing between the player and the aliens.
+//!
+//! Bunkers never move, they only wear away. Every bullet that runs into one, the
+//! player's or an alien's, knocks out the pixel it hit, and aliens that make it
+//! down to the bunkers' rows plow through whatever they touch.
+use crate::config;
+use crate::engine::sprite::Sprite;
+use crate::engine::Coordinate;
+use crate::engine::Engine;
+use crate::engine::EntityId;
+use crate::errors::Error;
+
+use crate::PALETTE;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Look of a bunker, `#` being a pixel
+const SHAPE: [&str; 3] = [".#####.", "#######", "##...##"];
+/// Rows left free between the bunkers and the shooter
+const CLEARANCE: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct Bunker {
+    sprite: Sprite,
+}
+
+impl Bunker {
+    /// Initializer function, placing the bunker's top left corner at the coordinate
+    pub fn new(engine: Rc<RefCell<Engine>>, (x, y): Coordinate) -> Result<Self, Error> {
+        let mut position = Vec::new();
+        for (dy, row) in SHAPE.iter().enumerate() {
+            for (dx, c) in row.chars().enumerate() {
+                if c == '#' {
+                    position.push((x + dx, y + dy));
+                }
+            }
+        }
+        Ok(Self {
+            sprite: Sprite::new(engine, position, 0.0, 0.0)?.with_glyph(PALETTE.bunker),
+        })
+    }
+
+    pub fn width() -> usize {
+        SHAPE[0].len()
+    }
+
+    pub fn height() -> usize {
+        SHAPE.len()
+    }
+
+    pub fn spawn(&mut self) -> Result<(), Error> {
+        let _ = self.sprite.spawn()?;
+        Ok(())
+    }
+
+    pub fn is_spawned(&self) -> bool {
+        self.sprite.is_spawned()
+    }
+
+    /// Returns the id owning its pixels in the engine
+    pub fn id(&self) -> EntityId {
+        self.sprite.id()
+    }
+
+    /// Knocks out the pixel at the coordinate, the bunker is destroyed
+    /// once it has none left. Returns false if the pixel isn't part of it
+    pub fn erode(&mut self, coordinate: Coordinate) -> bool {
+        if self.sprite.is_destroyed() || self.sprite.pop(coordinate).is_err() {
+            return false;
+        }
+        if self.sprite.coordinates().is_empty() {
+            self.destroy();
+        }
+        true
+    }
+
+    pub fn destroy(&mut self) {
+        let _ = self.sprite.destroy();
+    }
+}
+
+/// Spawns `bunkers.count` bunkers spread evenly across the plane,
+/// a few rows above the given row the shooter's top is on
+pub fn spawn_bunkers(engine: Rc<RefCell<Engine>>, shooter_top: usize) -> Vec<Bunker> {
+    let width = { engine.borrow().width };
+    let count = config::get().bunker_count;
+    let y = match shooter_top.checked_sub(CLEARANCE + Bunker::height()) {
+        Some(y) => y,
+        None => return Vec::new(),
+    };
+    if count == 0 || count * (Bunker::width() + 1) > width {
+        // not enough room for the bunkers to stand apart
+        return Vec::new();
+    }
+    let mut bunkers = Vec::new();
+    for i in 0..count {
+        let center = (i + 1) * width / (count + 1);
+        let x = center.saturating_sub(Bunker::width() / 2);
+        if let Ok(mut bunker) = Bunker::new(engine.clone(), (x, y)) {
+            // a bunker that would land on something is left out
+            if bunker.spawn().is_ok() {
+                bunkers.push(bunker);
+            }
+        }
+    }
+    bunkers
+}
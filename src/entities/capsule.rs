@@ -0,0 +1,81 @@
+//! Power-up capsules dropped by the aliens.
+//!
+//! A capsule falls straight down, just like an obstacle, with the symbol of the
+//! power-up it holds written on it. The shooter catches it by touching it, while
+//! anything else it lands on, or the bottom of the plane, breaks it.
+use crate::engine::sprite::Collision;
+use crate::engine::sprite::Sprite;
+use crate::engine::sprite::State;
+use crate::engine::Coordinate;
+use crate::engine::Engine;
+use crate::engine::EntityId;
+use crate::errors::{Error, ErrorKind};
+use crate::powerups::PowerUp;
+
+use crate::PALETTE;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Debug, Clone)]
+pub struct Capsule {
+    sprite: Sprite,
+    power_up: PowerUp,
+}
+
+impl Capsule {
+    pub fn new(
+        engine: Rc<RefCell<Engine>>,
+        position: Coordinate,
+        power_up: PowerUp,
+        velocity: f32,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            sprite: Sprite::new(engine, vec![position], velocity, velocity)?
+                .with_glyph(PALETTE.capsule.with_char(power_up.symbol())),
+            power_up,
+        })
+    }
+
+    /// Returns the power-up it holds
+    pub fn power_up(&self) -> PowerUp {
+        self.power_up
+    }
+
+    pub fn spawn(&mut self) -> Result<(), Error> {
+        let _ = self.sprite.spawn()?;
+        Ok(())
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.sprite.is_destroyed()
+    }
+
+    /// Returns the id owning its pixels in the engine
+    pub fn id(&self) -> EntityId {
+        self.sprite.id()
+    }
+
+    /// The update function, returns the collision with anything it landed on.
+    /// Once it falls off the bottom of the plane it's destroyed
+    pub fn step(&mut self, delta_time: f32) -> Option<Collision> {
+        if self.sprite.is_destroyed() {
+            return None;
+        }
+        match self.sprite.move_down(delta_time) {
+            Ok(State::Collided { coordinate, other }) => Some(Collision { coordinate, other }),
+            Ok(_) => None,
+            Err(error) => match error.kind() {
+                ErrorKind::OutOfBounds => {
+                    self.destroy();
+                    None
+                }
+                _ => None,
+            },
+        }
+    }
+
+    pub fn destroy(&mut self) {
+        let _ = self.sprite.destroy();
+    }
+}
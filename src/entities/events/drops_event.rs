@@ -1,20 +1,16 @@
 //! Drops events
+use crate::config;
+use crate::engine::sprite::Collision;
 use crate::engine::sprite::Sprite;
 use crate::engine::sprite::State;
-use crate::engine::Coordinate;
 use crate::engine::Engine;
 use crate::entities::Bullet;
 use crate::entities::Obstacle;
 use crate::errors::{Error, ErrorKind};
 use crate::utils;
 
-use crate::OBSTACLE_SPEED;
-use crate::OBSTACLE_WAIT_TIME;
-use crate::SPEEDSTER_STEP_PER_DELTA;
-
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::time::Instant;
 
 #[derive(Debug, Clone)]
 pub struct DropsEvent {
@@ -42,7 +38,7 @@ impl DropsEvent {
                 if let Ok(mut obstacle) = Obstacle::new(
                     engine.clone(),
                     vec![(i, height / 2), (i + 1, height / 2), (i + 2, height / 2)],
-                    OBSTACLE_SPEED,
+                    config::get().obstacle_speed,
                 ) {
                     obstacle.set_wait_time(2.0 + i as f32 / 10.0);
                     //obstacle.set_spawn_wait_time(2.0 + i as f32 / 7.0);
@@ -52,7 +48,7 @@ impl DropsEvent {
                 if let Ok(mut obstacle) = Obstacle::new(
                     engine.clone(),
                     vec![(i, height / 2), (i + 1, height / 2), (i + 2, height / 2)],
-                    OBSTACLE_SPEED,
+                    config::get().obstacle_speed,
                 ) {
                     obstacle.set_wait_time((width as f32 - i as f32) / 10.0 + 2.0);
                     //obstacle.set_spawn_wait_time(2.0 + i as f32 / 7.0);
@@ -71,6 +67,16 @@ impl DropsEvent {
         self.is_finished
     }
 
+    /// Takes every obstacle still on the plane off of it
+    pub fn destroy(&mut self) {
+        for obstacle in self.obstacles.iter_mut() {
+            if obstacle.is_spawned() {
+                obstacle.destroy();
+            }
+        }
+        self.obstacles.clear();
+    }
+
     pub fn deallocate_destroyed_obstacles(&mut self) {
         for i in (0..self.obstacles.len()).rev() {
             if self.obstacles[i].is_destroyed() {
@@ -79,7 +85,7 @@ impl DropsEvent {
         }
     }
 
-    pub fn move_obstacles(&mut self, delta_time: f32) -> Option<Coordinate> {
+    pub fn move_obstacles(&mut self, delta_time: f32) -> Option<Collision> {
         for o in self.obstacles.iter_mut() {
             let result = o.step(delta_time);
             if result.is_some() {
@@ -95,7 +101,7 @@ impl DropsEvent {
         }
     }
 
-    pub fn step(&mut self, delta_time: f32) -> Option<Coordinate> {
+    pub fn step(&mut self, delta_time: f32) -> Option<Collision> {
         if self.obstacles.is_empty() {
             self.is_finished = true;
             return None;
@@ -1,25 +1,22 @@
+use crate::engine::clock::Timer;
+use crate::engine::sprite::Collision;
 use crate::engine::sprite::Sprite;
 use crate::engine::sprite::State;
-use crate::engine::Coordinate;
 use crate::engine::Engine;
+use crate::engine::EntityId;
 use crate::entities::Bullet;
 use crate::entities::Obstacle;
 use crate::errors::{Error, ErrorKind};
 use crate::utils;
 
-use crate::OBSTACLE_SPEED;
-use crate::OBSTACLE_WAIT_TIME;
-use crate::SPEEDSTER_STEP_PER_DELTA;
-
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::time::Instant;
 
 #[derive(Clone, Debug)]
 pub struct SnowEvent {
     engine: Rc<RefCell<Engine>>,
     width: usize,
-    delay_timer: Instant,
+    delay_timer: Timer,
     obstacles: Vec<Obstacle>,
     spawn_time_delay: f32,
     total_rows: usize,
@@ -30,14 +27,17 @@ pub struct SnowEvent {
 
 impl SnowEvent {
     pub fn new(engine: Rc<RefCell<Engine>>) -> Self {
-        let width = { engine.borrow().width };
+        let (width, clock) = {
+            let eng = engine.borrow();
+            (eng.width, eng.clock())
+        };
         Self {
             engine,
             width,
             rows_spawned: 0,
             //total_rows: 10,
             total_rows: 5,
-            delay_timer: Instant::now(),
+            delay_timer: Timer::start(&clock),
             spawn_time_delay: 1.7,
             obstacles: Vec::new(),
             col_d: 9,
@@ -66,14 +66,15 @@ impl SnowEvent {
             7 => self.col_d = 9,
             _ => panic!("CRITICAL ERROR HAS OCCURED IN THE SNOW EVENT"),
         }
-        self.delay_timer = Instant::now();
+        let clock = { self.engine.borrow().clock() };
+        self.delay_timer.reset(&clock);
     }
 
     pub fn is_ready_to_spawn_row(&self) -> bool {
         if self.rows_spawned == self.total_rows {
             return false;
         }
-        if self.delay_timer.elapsed().as_secs_f32() < self.spawn_time_delay {
+        if self.delay_timer.elapsed(&self.engine.borrow().clock()) < self.spawn_time_delay {
             return false;
         }
         true
@@ -83,6 +84,16 @@ impl SnowEvent {
         self.is_finished
     }
 
+    /// Takes every snowflake still on the plane off of it
+    pub fn destroy(&mut self) {
+        for obstacle in self.obstacles.iter_mut() {
+            if obstacle.is_spawned() {
+                obstacle.destroy();
+            }
+        }
+        self.obstacles.clear();
+    }
+
     pub fn deallocate_destroyed_obstacles(&mut self) {
         for i in (0..self.obstacles.len()).rev() {
             if self.obstacles[i].is_destroyed() {
@@ -91,7 +102,7 @@ impl SnowEvent {
         }
     }
 
-    pub fn move_obstacles(&mut self, delta_time: f32) -> Option<Coordinate> {
+    pub fn move_obstacles(&mut self, delta_time: f32) -> Option<Collision> {
         for flake in self.obstacles.iter_mut() {
             let result = flake.step(delta_time);
             if result.is_some() {
@@ -102,7 +113,18 @@ impl SnowEvent {
         None
     }
 
-    pub fn step(&mut self, delta_time: f32) -> Option<Coordinate> {
+    /// Destroys the snowflake with the id, returns false if there is none
+    pub fn shoot_down(&mut self, id: EntityId) -> bool {
+        for flake in self.obstacles.iter_mut() {
+            if !flake.is_destroyed() && flake.id() == id {
+                flake.destroy();
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn step(&mut self, delta_time: f32) -> Option<Collision> {
         if self.rows_spawned == self.total_rows && self.obstacles.is_empty() {
             self.is_finished = true;
         }
@@ -1,7 +1,7 @@
 //! Zig Zag event for stage 3
+use crate::engine::sprite::Collision;
 use crate::engine::sprite::Sprite;
 use crate::engine::sprite::State;
-use crate::engine::Coordinate;
 use crate::engine::Engine;
 use crate::entities::Bullet;
 use crate::entities::Obstacle;
@@ -9,13 +9,8 @@ use crate::entities::Speedster;
 use crate::errors::{Error, ErrorKind};
 use crate::utils;
 
-use crate::OBSTACLE_SPEED;
-use crate::OBSTACLE_WAIT_TIME;
-use crate::SPEEDSTER_STEP_PER_DELTA;
-
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::time::Instant;
 
 // perhapss this
 #[derive(Debug, Clone)]
@@ -61,13 +56,13 @@ impl ZigZagEvent {
         self.is_finished
     }
 
-    fn move_down(&mut self, delta_time: f32) -> Option<Coordinate> {
+    fn move_down(&mut self, delta_time: f32) -> Option<Collision> {
         let mut speedster = self.speedster.borrow_mut();
         match speedster.move_down(delta_time) {
             Ok(state) => match state {
-                State::Collided(coordinate) => {
+                State::Collided { coordinate, other } => {
                     self.is_finished = true;
-                    return Some(coordinate);
+                    return Some(Collision { coordinate, other });
                 }
                 _ => (),
             },
@@ -104,7 +99,7 @@ impl ZigZagEvent {
         false
     }
 
-    pub fn step(&mut self, delta_time: f32) -> Option<Coordinate> {
+    pub fn step(&mut self, delta_time: f32) -> Option<Collision> {
         let position = self.position();
         if position % 33 == 0 && position != 0 {
             let result = self.move_down(delta_time);
@@ -119,9 +114,9 @@ impl ZigZagEvent {
         };
         return match result {
             Ok(state) => match state {
-                State::Collided(coordinate) => {
+                State::Collided { coordinate, other } => {
                     self.is_finished = true;
-                    Some(coordinate)
+                    Some(Collision { coordinate, other })
                 }
                 _ => None,
             },
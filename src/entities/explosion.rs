@@ -0,0 +1,108 @@
+//! Explosions, debris flying off whatever was hit.
+//!
+//! An explosion plays a few frames around the coordinate of the hit and then
+//! vanishes. It's drawn on a layer of the engine that doesn't collide, so it
+//! never gets in the way of a bullet or an alien.
+use crate::engine::clock::{Clock, Timer};
+use crate::engine::sprite::Sprite;
+use crate::engine::Coordinate;
+use crate::engine::Engine;
+use crate::engine::LayerId;
+
+use crate::PALETTE;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Frames of the animation, centered on the hit, a space leaving the plane as it is
+const FRAMES: [[&str; 3]; 4] = [
+    ["   ", " * ", "   "],
+    [" ' ", "-*-", " , "],
+    ["` '", " + ", ", ."],
+    [". .", "   ", ". ."],
+];
+/// Seconds every frame is shown for
+const FRAME_DURATION: f32 = 0.08;
+
+#[derive(Debug, Clone)]
+pub struct Explosion {
+    engine: Rc<RefCell<Engine>>,
+    /// layer the frames are drawn on
+    layer: LayerId,
+    center: Coordinate,
+    timer: Timer,
+    /// frame shown, `None` once the animation is over
+    frame: Option<usize>,
+    /// one sprite per character of the frame shown
+    debris: Vec<Sprite>,
+}
+
+impl Explosion {
+    /// Initializer function, the first frame is drawn right away on the given layer
+    pub fn new(engine: Rc<RefCell<Engine>>, layer: LayerId, center: Coordinate) -> Self {
+        let clock = { engine.borrow().clock() };
+        let mut explosion = Self {
+            engine,
+            layer,
+            center,
+            timer: Timer::start(&clock),
+            frame: None,
+            debris: Vec::new(),
+        };
+        explosion.show(0);
+        explosion
+    }
+
+    /// Moves the animation on to the frame due, must be called every update
+    pub fn update(&mut self, clock: &Clock) {
+        let due = (self.timer.elapsed(clock) / FRAME_DURATION) as usize;
+        if self.frame.is_some_and(|frame| frame != due) {
+            self.show(due);
+        }
+    }
+
+    /// Returns true once every frame was played
+    pub fn is_finished(&self) -> bool {
+        self.frame.is_none()
+    }
+
+    /// Wipes the frame shown, ending the animation
+    pub fn destroy(&mut self) {
+        for mut sprite in self.debris.drain(..) {
+            let _ = sprite.destroy();
+        }
+        self.frame = None;
+    }
+
+    /// Replaces the frame shown with the given one, leaving out
+    /// the parts over the plane's edges
+    fn show(&mut self, frame: usize) {
+        self.destroy();
+        let rows = match FRAMES.get(frame) {
+            Some(rows) => rows,
+            None => return,
+        };
+        let (width, height) = {
+            let eng = self.engine.borrow();
+            (eng.width, eng.height)
+        };
+        for (dy, row) in rows.iter().enumerate() {
+            for (dx, c) in row.chars().enumerate() {
+                let x = (self.center.0 + dx).checked_sub(1);
+                let y = (self.center.1 + dy).checked_sub(1);
+                let (x, y) = match (x, y) {
+                    (Some(x), Some(y)) if x < width && y < height && c != ' ' => (x, y),
+                    _ => continue,
+                };
+                let glyph = PALETTE.explosion.with_char(c);
+                if let Ok(sprite) = Sprite::new(self.engine.clone(), vec![(x, y)], 0.0, 0.0) {
+                    let mut sprite = sprite.with_layer(self.layer).with_glyph(glyph);
+                    if sprite.spawn().is_ok() {
+                        self.debris.push(sprite);
+                    }
+                }
+            }
+        }
+        self.frame = Some(frame);
+    }
+}
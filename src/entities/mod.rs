@@ -1,16 +1,24 @@
 mod aliens;
 mod bullet;
+mod bunker;
+mod capsule;
 mod events;
+mod explosion;
 mod obstacle;
 mod shooter;
 mod speedster;
+mod ufo;
 
 pub use aliens::{
-    farthest_left_alien, farthest_right_alien, find_alien_and_destroy, spawn_aliens, Alien,
-    Direction,
+    farthest_left_alien, farthest_right_alien, find_alien_and_destroy, find_alien_and_hit,
+    front_line, spawn_aliens, split_alien, Alien, AlienHit, Direction,
 };
 pub use bullet::Bullet;
+pub use bunker::{spawn_bunkers, Bunker};
+pub use capsule::Capsule;
 pub use events::SnowEvent;
+pub use explosion::Explosion;
 pub use obstacle::Obstacle;
 pub use shooter::Shooter;
 pub use speedster::Speedster;
+pub use ufo::{BonusLabel, Ufo};
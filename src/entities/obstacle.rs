@@ -1,28 +1,29 @@
 //! Obstacle sprites for the last stage of the end game.
 //!
 //! Obstacles in this game only spawn and drop downwards, trying to hit the player.
+use crate::config;
+use crate::engine::clock::{Clock, Timer};
+use crate::engine::sprite::Collision;
 use crate::engine::sprite::Sprite;
 use crate::engine::sprite::State;
 use crate::engine::Coordinate;
 use crate::engine::Engine;
+use crate::engine::EntityId;
 use crate::entities::Bullet;
 use crate::errors::{Error, ErrorKind};
 use crate::utils;
 
-use crate::OBSTACLE_SPEED;
-use crate::OBSTACLE_WAIT_TIME;
-use crate::SPEEDSTER_STEP_PER_DELTA;
+use crate::PALETTE;
 
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::time::Instant;
 
 #[derive(Debug, Clone)]
 pub struct Obstacle {
     sprite: Sprite,
     spawn_wait_time: f32,
-    spawn_timer: Instant,
-    wait_timer: Instant,
+    spawn_timer: Timer,
+    wait_timer: Timer,
     wait_time: f32,
     destroy_on_contact: bool,
 }
@@ -33,16 +34,22 @@ impl Obstacle {
         position: Vec<Coordinate>,
         velocity: f32,
     ) -> Result<Self, Error> {
+        let clock = { engine.borrow().clock() };
         Ok(Self {
             spawn_wait_time: 0.0,
-            spawn_timer: Instant::now(),
-            wait_timer: Instant::now(),
-            wait_time: OBSTACLE_WAIT_TIME,
-            sprite: Sprite::new(engine, position, velocity, velocity)?,
+            spawn_timer: Timer::start(&clock),
+            wait_timer: Timer::start(&clock),
+            wait_time: config::get().obstacle_wait_time,
+            sprite: Sprite::new(engine, position, velocity, velocity)?.with_glyph(PALETTE.obstacle),
             destroy_on_contact: false,
         })
     }
 
+    /// Returns the current time of the simulation the obstacle lives in
+    fn clock(&self) -> Clock {
+        self.sprite.engine().borrow().clock()
+    }
+
     pub fn set_spawn_wait_time(&mut self, s: f32) {
         self.spawn_wait_time = s;
     }
@@ -64,7 +71,7 @@ impl Obstacle {
 
     pub fn is_ready_to_spawn(&mut self) -> bool {
         // returns
-        self.spawn_timer.elapsed().as_secs_f32() > self.spawn_wait_time
+        self.spawn_timer.elapsed(&self.clock()) > self.spawn_wait_time
     }
 
     pub fn set_velocity(&mut self, velocity: f32) {
@@ -77,23 +84,26 @@ impl Obstacle {
     }
 
     pub fn is_wait_time_expired(&self) -> bool {
-        if self.wait_timer.elapsed().as_secs_f32() >= self.wait_time {
+        if self.wait_timer.elapsed(&self.clock()) >= self.wait_time {
             return true;
         }
         false
     }
 
     pub fn reset_wait_timer(&mut self) {
-        self.wait_timer = Instant::now();
+        let clock = self.clock();
+        self.wait_timer.reset(&clock);
     }
 
     pub fn reset_spawn_timer(&mut self) {
-        self.spawn_timer = Instant::now();
+        let clock = self.clock();
+        self.spawn_timer.reset(&clock);
     }
 
     pub fn reset_timer(&mut self) {
-        self.wait_timer = Instant::now();
-        self.spawn_timer = Instant::now();
+        let clock = self.clock();
+        self.wait_timer.reset(&clock);
+        self.spawn_timer.reset(&clock);
     }
 
     /// Because the obstacles drop downwards after a given time,
@@ -103,7 +113,7 @@ impl Obstacle {
     }
 
     /// The update function for obstacles
-    pub fn step(&mut self, delta_time: f32) -> Option<Coordinate> {
+    pub fn step(&mut self, delta_time: f32) -> Option<Collision> {
         if self.is_destroyed() {
             return None;
         }
@@ -112,11 +122,11 @@ impl Obstacle {
         }
         return match self.sprite.move_down(delta_time) {
             Ok(state) => match state {
-                State::Collided(coordinate) => {
+                State::Collided { coordinate, other } => {
                     if self.destroy_on_contact {
                         self.destroy();
                     }
-                    Some(coordinate)
+                    Some(Collision { coordinate, other })
                 }
                 _ => None,
             },
@@ -134,6 +144,11 @@ impl Obstacle {
         self.sprite.is_destroyed()
     }
 
+    /// Returns the id owning its pixels in the engine
+    pub fn id(&self) -> EntityId {
+        self.sprite.id()
+    }
+
     pub fn destroy(&mut self) {
         let _ = self.sprite.destroy();
     }
@@ -1,16 +1,40 @@
 /// Logic for the shooter sprite. The sprite which the player can move.
+///
+/// The shooter has a number of lives. Every hit costs one, destroying the sprite
+/// for a moment before it respawns at its start position, unable to be hit for a
+/// short while.
+use crate::config;
+use crate::engine::clock::Timer;
+use crate::engine::sprite::Collision;
 use crate::engine::sprite::Sprite;
 use crate::engine::sprite::State;
 use crate::engine::Coordinate;
 use crate::engine::Engine;
+use crate::engine::EntityId;
 use crate::errors::{Error, ErrorKind};
+use crate::input::Action;
+use crate::PALETTE;
 
 use std::cell::RefCell;
 use std::rc::Rc;
 
+/// seconds the shooter stays destroyed before respawning
+const RESPAWN_DELAY: f32 = 1.0;
+/// seconds between each flash while invulnerable
+const FLASH_INTERVAL: f32 = 0.1;
+/// most lives the shooter can have
+const MAX_LIVES: usize = 9;
+
 #[derive(Debug, Clone)]
 pub struct Shooter {
     sprite: Sprite,
+    /// position the shooter respawns at
+    start_position: Vec<Coordinate>,
+    lives: usize,
+    /// running while the shooter waits to respawn
+    respawn_timer: Option<Timer>,
+    /// running while the shooter can't be hit
+    invulnerability_timer: Option<Timer>,
 }
 
 impl Shooter {
@@ -20,50 +44,140 @@ impl Shooter {
         velocity: f32,
     ) -> Result<Self, Error> {
         Ok(Self {
-            sprite: Sprite::new(engine, position, velocity, velocity)?,
+            sprite: Sprite::new(engine, position.clone(), velocity, velocity)?
+                .with_glyph(PALETTE.shooter),
+            start_position: position,
+            lives: config::get().shooter_lives,
+            respawn_timer: None,
+            invulnerability_timer: None,
         })
     }
 
+    /// Returns the lives left
+    pub fn lives(&self) -> usize {
+        self.lives
+    }
+
+    /// Gives the shooter one more life, up to `MAX_LIVES`
+    pub fn add_life(&mut self) {
+        if !self.is_dead() {
+            self.lives = (self.lives + 1).min(MAX_LIVES);
+        }
+    }
+
+    /// Returns true once every life is gone
+    pub fn is_dead(&self) -> bool {
+        self.lives == 0
+    }
+
+    /// Returns true while the shooter waits to respawn
+    pub fn is_respawning(&self) -> bool {
+        self.respawn_timer.is_some()
+    }
+
+    /// Returns true while hits are ignored
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerability_timer.is_some()
+    }
+
+    /// Takes a life away and destroys the sprite until it respawns.
+    ///
+    /// Returns false if the hit was ignored because the shooter is
+    /// invulnerable, waiting to respawn or already dead
+    pub fn hit(&mut self) -> bool {
+        if self.is_dead() || self.is_respawning() || self.is_invulnerable() {
+            return false;
+        }
+        self.lives -= 1;
+        self.sprite.destroy();
+        if !self.is_dead() {
+            let clock = self.sprite.engine().borrow().clock();
+            self.respawn_timer = Some(Timer::start(&clock));
+        }
+        true
+    }
+
+    /// Respawns the shooter once its delay is over and makes it
+    /// flash while invulnerable, must be called every update
+    pub fn update(&mut self) {
+        let clock = self.sprite.engine().borrow().clock();
+        if let Some(timer) = self.respawn_timer {
+            if timer.elapsed(&clock) < RESPAWN_DELAY {
+                return;
+            }
+            self.sprite
+                .coordinates_mut()
+                .copy_from_slice(&self.start_position);
+            self.sprite.recalc_bounding_box();
+            if self.sprite.spawn().is_err() {
+                // something is in the way, trying again on the next update
+                return;
+            }
+            self.respawn_timer = None;
+            self.invulnerability_timer = Some(Timer::start(&clock));
+        }
+        if let Some(timer) = self.invulnerability_timer {
+            let elapsed = timer.elapsed(&clock);
+            let glyph = if elapsed >= config::get().shooter_invulnerability {
+                self.invulnerability_timer = None;
+                PALETTE.shooter
+            } else if (elapsed / FLASH_INTERVAL) as usize % 2 == 0 {
+                PALETTE.shooter_invulnerable
+            } else {
+                PALETTE.shooter
+            };
+            if self.sprite.glyph() != glyph {
+                self.sprite.set_glyph(glyph);
+            }
+        }
+    }
+
     /// Returns its X position
     pub fn x(&self) -> usize {
         self.sprite.far_left()
     }
 
+    /// Returns the row its top is on
+    pub fn far_top(&self) -> usize {
+        self.sprite.far_top()
+    }
+
+    /// Returns the coordinate in the middle of its bottom row
+    pub fn center(&self) -> Coordinate {
+        (self.head().0, self.sprite.far_bottom())
+    }
+
     /// Returns an array of coordinates; position.
     pub fn coordinates(&self) -> &[Coordinate] {
         self.sprite.coordinates()
     }
 
     /// Returns all x values for its horizontal position
-    pub fn xs(&self) -> [usize; 3] {
-        let mut x: [usize; 3] = [0, 0, 0];
-        let fl = self.sprite.far_left();
-        x[0] = fl;
-        x[1] = fl - 1;
-        x[2] = fl - 2;
-        x
+    pub fn xs(&self) -> Vec<usize> {
+        (self.sprite.far_left()..=self.sprite.far_right()).collect()
     }
 
     pub fn spawn(&mut self) {
         let _ = self.sprite.spawn();
     }
 
-    pub fn contains(&mut self, coordinate: Coordinate) -> bool {
-        self.sprite.contains(coordinate)
+    /// Returns the id owning its pixels in the engine
+    pub fn id(&self) -> EntityId {
+        self.sprite.id()
     }
 
     /// The update function
-    pub fn step(&mut self, key: &str, delta_time: f32) -> Option<Coordinate> {
+    pub fn step(&mut self, action: Action, delta_time: f32) -> Option<Collision> {
         // moving the sprite
-        let result: Result<State, Error> = match key {
-            "left" => self.sprite.move_left(delta_time),
-            "right" => self.sprite.move_right(delta_time),
+        let result: Result<State, Error> = match action {
+            Action::MoveLeft => self.sprite.move_left(delta_time),
+            Action::MoveRight => self.sprite.move_right(delta_time),
             _ => return None,
         };
         // dealing with the movement result
         return match result {
             Ok(state) => match state {
-                State::Collided(coordinate) => Some(coordinate),
+                State::Collided { coordinate, other } => Some(Collision { coordinate, other }),
                 _ => None,
             },
             Err(_) => None,
@@ -3,24 +3,29 @@
 //! The speedster has 3 lives, each with a different action.
 //! On the 3rd life, the speedster is able to spawn obstacles which
 //! makes it harder for the player to kill.
+use crate::config;
+use crate::engine::clock::Timer;
+use crate::engine::sprite::Collision;
 use crate::engine::sprite::Sprite;
 use crate::engine::sprite::State;
 use crate::engine::Coordinate;
 use crate::engine::Engine;
+use crate::engine::EntityId;
 use crate::entities::events::{DropsEvent, SnowEvent, ZigZagEvent};
 use crate::entities::Bullet;
 use crate::entities::Obstacle;
 use crate::errors::{Error, ErrorKind};
 use crate::utils;
 
-use crate::BULLET_STEP_PER_DELTA;
-use crate::OBSTACLE_SPEED;
-use crate::OBSTACLE_WAIT_TIME;
-use crate::SPEEDSTER_STEP_PER_DELTA;
+use crate::PALETTE;
 
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::time::Instant;
+
+/// seconds the speedster flashes for after being hit
+const FLASH_DURATION: f32 = 1.0;
+/// seconds between each flash
+const FLASH_INTERVAL: f32 = 0.1;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum XDirection {
@@ -56,6 +61,8 @@ pub struct Speedster {
     stage_3_initialized: bool,
     zigzag_event: ZigZagEvent,
     engine: Rc<RefCell<Engine>>,
+    /// started when the speedster is hit, `None` when not flashing
+    flash_timer: Option<Timer>,
 }
 
 impl Speedster {
@@ -64,12 +71,15 @@ impl Speedster {
             let eng = engine.borrow();
             (eng.width, eng.height - eng.height / 3)
         };
-        let mut sprite = Rc::new(RefCell::new(Sprite::new(
-            engine.clone(),
-            vec![(width - 1, 1), (width - 2, 1), (width - 3, 1)],
-            velocity,       // horizontal velocity
-            velocity / 2.0, // vertical velocity
-        )?));
+        let mut sprite = Rc::new(RefCell::new(
+            Sprite::new(
+                engine.clone(),
+                vec![(width - 1, 1), (width - 2, 1), (width - 3, 1)],
+                velocity,       // horizontal velocity
+                velocity / 2.0, // vertical velocity
+            )?
+            .with_glyph(PALETTE.speedster),
+        ));
         Ok(Self {
             snow_event: SnowEvent::new(engine.clone()),
             zigzag_event: ZigZagEvent::new(sprite.clone(), width),
@@ -84,6 +94,7 @@ impl Speedster {
             xdirection: XDirection::Left,
             ydirection: YDirection::Down,
             stage_3_initialized: false,
+            flash_timer: None,
         })
     }
 
@@ -122,8 +133,9 @@ impl Speedster {
         let _ = self.sprite.borrow_mut().spawn();
     }
 
-    pub fn contains(&self, coordinate: Coordinate) -> bool {
-        self.sprite.borrow().contains(coordinate)
+    /// Returns the id owning its pixels in the engine
+    pub fn id(&self) -> EntityId {
+        self.sprite.borrow().id()
     }
 
     /// Resets the sprites position to being at the
@@ -141,7 +153,7 @@ impl Speedster {
     }
 
     /// Stage 1, the sprite moves left to right and right to left.
-    pub fn stage_1(&mut self, delta_time: f32) -> Option<Coordinate> {
+    pub fn stage_1(&mut self, delta_time: f32) -> Option<Collision> {
         let mut sprite = self.sprite.borrow_mut();
         // encapsulating the movement methods in a single function for more
         // consice code
@@ -153,11 +165,11 @@ impl Speedster {
         return match movement_result {
             Ok(state) => match state {
                 // collided with a bullet
-                State::Collided(coordinate) => {
+                State::Collided { coordinate, other } => {
                     // Modifying velocity for the next stage
                     let orig_vel = sprite.x_velocity();
                     sprite.set_x_velocity(orig_vel - (orig_vel / 5.0));
-                    Some(coordinate)
+                    Some(Collision { coordinate, other })
                 }
                 _ => None,
             },
@@ -176,7 +188,7 @@ impl Speedster {
     }
 
     /// Stage 2, the sprite moves side to side and ocassionally downwards
-    pub fn stage_2(&mut self, delta_time: f32) -> Option<Coordinate> {
+    pub fn stage_2(&mut self, delta_time: f32) -> Option<Collision> {
         let mut sprite = self.sprite.borrow_mut();
         let result: Result<State, Error> = match self.xdirection {
             XDirection::Left => {
@@ -236,11 +248,11 @@ impl Speedster {
         };
         return match result {
             Ok(state) => match state {
-                State::Collided(coordinate) => {
+                State::Collided { coordinate, other } => {
                     // Speedster was previously slowed down,
                     // this returns it to its normal speed.
-                    sprite.set_x_velocity(SPEEDSTER_STEP_PER_DELTA);
-                    Some(coordinate)
+                    sprite.set_x_velocity(config::get().speedster_step_per_delta);
+                    Some(Collision { coordinate, other })
                 }
                 _ => None,
             },
@@ -285,14 +297,18 @@ impl Speedster {
         if fr + 2 <= self.width - 1 {
             guard_position.extend([(fr + 4, 0), (fr + 4, 1), (fr + 4, 2), (fr + 4, 3)]);
         }
-        let mut obstacle = Obstacle::new(self.engine.clone(), guard_position, OBSTACLE_SPEED)?;
+        let mut obstacle = Obstacle::new(
+            self.engine.clone(),
+            guard_position,
+            config::get().obstacle_speed,
+        )?;
         obstacle.set_wait_time(1000.0); // making the obstacle last a while
         obstacle.spawn();
         Ok(obstacle)
     }
 
     /// Updates all obstacles
-    fn run_obstacles(&mut self, delta_time: f32) -> Option<Coordinate> {
+    fn run_obstacles(&mut self, delta_time: f32) -> Option<Collision> {
         for o in self.obstacles.iter_mut() {
             let result = o.step(delta_time);
             if result.is_some() {
@@ -311,7 +327,8 @@ impl Speedster {
         let engine_ptr = speedster.engine();
         let engine = engine_ptr.borrow();
         for y in speedster.far_bottom() + 1..engine.height {
-            if engine.is_on(&(x, y)) && bullets.iter().all(|b| !b.contains((x, y))) {
+            let blocker = engine.blocker(&(x, y));
+            if blocker.is_some_and(|id| bullets.iter().all(|b| b.id() != id)) {
                 return true;
             }
         }
@@ -319,17 +336,17 @@ impl Speedster {
     }
 
     /// Making sure an alien bullet isn't destroyed after colliding with another alien bullet
-    fn eval_with_bullets(&self, bullets: &[Bullet], coordinate: &Coordinate) -> Option<Coordinate> {
+    fn eval_with_bullets(&self, bullets: &[Bullet], collision: Collision) -> Option<Collision> {
         for b in bullets.iter() {
-            if b.is_alien_bullet() && b.contains(*coordinate) {
+            if b.is_alien_bullet() && b.id() == collision.other {
                 return None;
             }
         }
-        Some(*coordinate)
+        Some(collision)
     }
 
     /// Stage 3, where the speedster starts spawning obstacles
-    pub fn stage_3(&mut self, delta_time: f32, bullets: &mut Vec<Bullet>) -> Option<Coordinate> {
+    pub fn stage_3(&mut self, delta_time: f32, bullets: &mut Vec<Bullet>) -> Option<Collision> {
         //let mut guard: Obstacle;
         if !self.stage_3_initialized {
             self.s3_place_speedster_in_center_x();
@@ -342,27 +359,24 @@ impl Speedster {
             self.stage_3_initialized = true;
         }
         if !self.snow_event.is_finished() {
-            if let Some(coordinate) = self.snow_event.step(delta_time) {
-                //return Some(coordinate);
-                return self.eval_with_bullets(&bullets, &coordinate);
+            if let Some(collision) = self.snow_event.step(delta_time) {
+                return self.eval_with_bullets(bullets, collision);
             }
         } else if self.snow_event.is_finished() && !self.obstacles[0].is_destroyed() {
             self.obstacles[0].let_drop(); // just a trigger function that sets the wait time to 0.0
         } else if !self.drops_event.is_finished() {
-            if let Some(coordinate) = self.drops_event.step(delta_time) {
-                //return Some(coordinate);
-                return self.eval_with_bullets(&bullets, &coordinate);
+            if let Some(collision) = self.drops_event.step(delta_time) {
+                return self.eval_with_bullets(bullets, collision);
             }
         } else if !self.zigzag_event.is_finished() {
-            if let Some(coordinate) = self.zigzag_event.step(delta_time) {
-                //return Some(coordinate);
-                return self.eval_with_bullets(&bullets, &coordinate);
+            if let Some(collision) = self.zigzag_event.step(delta_time) {
+                return self.eval_with_bullets(bullets, collision);
             }
             if self.has_obstruction(&bullets) {
                 if let Ok(b) = Bullet::new(
                     self.sprite.borrow().engine(),
                     self.head(),
-                    BULLET_STEP_PER_DELTA,
+                    config::get().bullet_step_per_delta,
                 ) {
                     let mut bullet = b.to_alien_bullet();
                     let _ = bullet.spawn();
@@ -373,9 +387,31 @@ impl Speedster {
         self.run_obstacles(delta_time)
     }
 
+    /// Alternates the speedster's glyph while it is flashing from a hit
+    fn flash(&mut self) {
+        let timer = match self.flash_timer {
+            Some(timer) => timer,
+            None => return,
+        };
+        let elapsed = timer.elapsed(&self.engine.borrow().clock());
+        let glyph = if elapsed >= FLASH_DURATION {
+            self.flash_timer = None;
+            PALETTE.speedster
+        } else if (elapsed / FLASH_INTERVAL) as usize % 2 == 0 {
+            PALETTE.speedster_hit
+        } else {
+            PALETTE.speedster
+        };
+        let mut sprite = self.sprite.borrow_mut();
+        if sprite.glyph() != glyph {
+            sprite.set_glyph(glyph);
+        }
+    }
+
     /// The update function
-    pub fn step(&mut self, delta_time: f32, bullets: &mut Vec<Bullet>) -> Option<Coordinate> {
-        let result: Option<Coordinate> = match self.state {
+    pub fn step(&mut self, delta_time: f32, bullets: &mut Vec<Bullet>) -> Option<Collision> {
+        self.flash();
+        let result: Option<Collision> = match self.state {
             EndGameState::Stage1 => self.stage_1(delta_time),
             EndGameState::Stage2 => self.stage_2(delta_time),
             EndGameState::Stage3 => self.stage_3(delta_time, bullets),
@@ -386,7 +422,7 @@ impl Speedster {
                 if let Ok(b) = Bullet::new(
                     self.sprite.borrow().engine(),
                     self.head(),
-                    BULLET_STEP_PER_DELTA,
+                    config::get().bullet_step_per_delta,
                 ) {
                     let mut bullet = b.to_alien_bullet();
                     let _ = bullet.spawn();
@@ -394,21 +430,24 @@ impl Speedster {
                 }
             }
         }
-        if let Some(coordinate) = result {
-            for b in bullets.iter() {
-                if b.is_alien_bullet() && b.contains(coordinate) {
-                    return None;
-                }
-            }
+        if let Some(collision) = result {
+            return self.eval_with_bullets(bullets, collision);
         }
         result
     }
 
+    /// Destroys the snowflake with the id, returns false if there is none
+    pub fn shoot_down_snowflake(&mut self, id: EntityId) -> bool {
+        self.snow_event.shoot_down(id)
+    }
+
     /// A function that allows for other entities to state that the speedster was hit
     pub fn was_hit(&mut self) {
         self.destroy();
         self.reset_position();
         self.next_stage();
+        let clock = { self.engine.borrow().clock() };
+        self.flash_timer = Some(Timer::start(&clock));
     }
 
     pub fn is_dead(&self) -> bool {
@@ -420,6 +459,21 @@ impl Speedster {
         let _ = sprite.destroy();
     }
 
+    /// Takes the speedster and everything it dropped off of the plane, once the boss fight is over
+    pub fn despawn(&mut self) {
+        if self.is_spawned() {
+            self.destroy();
+        }
+        for obstacle in self.obstacles.iter_mut() {
+            if obstacle.is_spawned() {
+                obstacle.destroy();
+            }
+        }
+        self.obstacles.clear();
+        self.snow_event.destroy();
+        self.drops_event.destroy();
+    }
+
     /// For readability purposes
     pub fn stages(&self) -> u8 {
         3
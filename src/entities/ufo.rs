@@ -0,0 +1,166 @@
+//! Mystery UFO, a bonus saucer crossing the top of the plane.
+//!
+//! Every so often, while the formation is still alive, a UFO flies across rows
+//! 0 and 1 from one side of the plane to the other. It never shoots, it's only
+//! there to be shot down for a random bonus, which is then shown for a moment
+//! where the UFO was, over anything else and in nobody's way.
+use crate::engine::clock::{Clock, Timer};
+use crate::engine::sprite::Collision;
+use crate::engine::sprite::Sprite;
+use crate::engine::sprite::State;
+use crate::engine::Coordinate;
+use crate::engine::Engine;
+use crate::engine::EntityId;
+use crate::engine::LayerId;
+use crate::entities::Direction;
+use crate::errors::{Error, ErrorKind};
+
+use crate::PALETTE;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Look of the UFO, `#` being a pixel
+const SHAPE: [&str; 2] = [".###.", "#####"];
+/// Seconds the bonus is shown for once the UFO is shot down
+const BONUS_DURATION: f32 = 1.5;
+
+#[derive(Debug, Clone)]
+pub struct Ufo {
+    sprite: Sprite,
+    direction: Direction,
+}
+
+impl Ufo {
+    /// Initializer function, the UFO comes in from the side
+    /// opposite to the direction it flies towards
+    pub fn new(
+        engine: Rc<RefCell<Engine>>,
+        direction: Direction,
+        velocity: f32,
+    ) -> Result<Self, Error> {
+        let width = { engine.borrow().width };
+        let x = match direction {
+            Direction::Right => 0,
+            Direction::Left => width - SHAPE[0].len(),
+        };
+        let mut position = Vec::new();
+        for (y, row) in SHAPE.iter().enumerate() {
+            for (dx, c) in row.chars().enumerate() {
+                if c == '#' {
+                    position.push((x + dx, y));
+                }
+            }
+        }
+        Ok(Self {
+            sprite: Sprite::new(engine, position, velocity, velocity)?.with_glyph(PALETTE.ufo),
+            direction,
+        })
+    }
+
+    pub fn spawn(&mut self) -> Result<(), Error> {
+        let _ = self.sprite.spawn()?;
+        Ok(())
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.sprite.is_destroyed()
+    }
+
+    /// Returns the id owning its pixels in the engine
+    pub fn id(&self) -> EntityId {
+        self.sprite.id()
+    }
+
+    /// Returns the coordinate in the middle of its bottom row
+    pub fn center(&self) -> Coordinate {
+        (
+            (self.sprite.far_right() - (self.sprite.far_right() - self.sprite.far_left()) / 2),
+            self.sprite.far_bottom(),
+        )
+    }
+
+    /// The update function, returns the collision with anything it ran into.
+    /// Once it reaches the far side of the plane it flies off and is destroyed
+    pub fn step(&mut self, delta_time: f32) -> Option<Collision> {
+        if self.sprite.is_destroyed() {
+            return None;
+        }
+        let movement_result: Result<State, Error> = match self.direction {
+            Direction::Left => self.sprite.move_left(delta_time),
+            Direction::Right => self.sprite.move_right(delta_time),
+        };
+        match movement_result {
+            Ok(State::Collided { coordinate, other }) => Some(Collision { coordinate, other }),
+            Ok(_) => None,
+            Err(error) => match error.kind() {
+                ErrorKind::OutOfBounds => {
+                    // crossed the whole plane
+                    self.destroy();
+                    None
+                }
+                _ => None,
+            },
+        }
+    }
+
+    pub fn destroy(&mut self) {
+        let _ = self.sprite.destroy();
+    }
+}
+
+/// The points a UFO was worth, written on the plane where it was shot down
+#[derive(Debug, Clone)]
+pub struct BonusLabel {
+    /// one sprite per digit, each drawn with its own character
+    digits: Vec<Sprite>,
+    timer: Timer,
+}
+
+impl BonusLabel {
+    /// Writes the points centered on the coordinate, on the given layer,
+    /// leaving out the digits that would go over the plane's edges
+    pub fn new(
+        engine: Rc<RefCell<Engine>>,
+        layer: LayerId,
+        points: u64,
+        (x, y): Coordinate,
+    ) -> Self {
+        let (width, clock) = {
+            let eng = engine.borrow();
+            (eng.width, eng.clock())
+        };
+        let text = points.to_string();
+        let left = x.saturating_sub(text.len() / 2);
+        let mut digits = Vec::new();
+        for (i, c) in text.chars().enumerate() {
+            if left + i >= width {
+                break;
+            }
+            let glyph = PALETTE.ufo_bonus.with_char(c);
+            if let Ok(sprite) = Sprite::new(engine.clone(), vec![(left + i, y)], 0.0, 0.0) {
+                let mut sprite = sprite.with_layer(layer).with_glyph(glyph);
+                if sprite.spawn().is_ok() {
+                    digits.push(sprite);
+                }
+            }
+        }
+        Self {
+            digits,
+            timer: Timer::start(&clock),
+        }
+    }
+
+    /// Returns true once the label was shown long enough
+    pub fn is_expired(&self, clock: &Clock) -> bool {
+        self.timer.elapsed(clock) >= BONUS_DURATION
+    }
+
+    pub fn destroy(&mut self) {
+        for digit in self.digits.iter_mut() {
+            if digit.is_spawned() {
+                let _ = digit.destroy();
+            }
+        }
+    }
+}
@@ -42,6 +42,10 @@ pub enum ErrorKind {
     InexistentSprite,
     InexistentCoordinate,
     OverlappingSprite,
+    InvalidArgument,
+    InvalidReplay,
+    InvalidConfig,
+    Io,
     Other,
 }
 
@@ -0,0 +1,183 @@
+//! Alien Fire Control
+//!
+//! Decides when the aliens fire and which one of them does, following these rules:
+//!
+//! - Rate: the aliens fire, all together, at most the wave's `fire_rate` shots a second.
+//! - Cap: no alien fires while the wave's `max_alien_bullets` alien bullets are in flight.
+//! - Cooldown: an alien that fired waits the wave's `fire_cooldown` seconds to fire again.
+//! - Front line: only aliens with no other alien below them fire.
+//! - Aim: with the wave's `aim` chance the shot comes from a front-line alien right
+//!   above the shooter, otherwise from any front-line alien at random.
+//!
+//! Every random pick is drawn from the game's seeded [`Rng`], so the same seed
+//! and input always fire the same shots. How the rules tighten from wave to
+//! wave is up to [`crate::wave::Wave`].
+//!
+//! # Example
+//! ```rust
+//! use crate::fire_control::FireControl;
+//!
+//! let mut fire_control = FireControl::new(&clock);
+//! if let Some(head) = fire_control.fire(&mut aliens, shooter.xs(), in_flight, &wave, &mut rng, &clock) {
+//!     // spawn an alien bullet at `head`
+//! }
+//! ```
+use crate::engine::clock::{Clock, Timer};
+use crate::engine::Coordinate;
+use crate::entities::{front_line, Alien};
+use crate::rng::Rng;
+use crate::wave::Wave;
+
+#[derive(Debug, Clone)]
+pub struct FireControl {
+    /// started on the last alien shot
+    timer: Timer,
+}
+
+impl FireControl {
+    pub fn new(clock: &Clock) -> Self {
+        Self {
+            timer: Timer::start(clock),
+        }
+    }
+
+    /// Picks the alien that fires now, if the rules let any, and reloads it.
+    /// Returns where its bullet should spawn
+    ///
+    /// `target` holds the columns the shooter is on and `in_flight`
+    /// how many alien bullets are already on the plane
+    pub fn fire(
+        &mut self,
+        aliens: &mut [Alien],
+        target: &[usize],
+        in_flight: usize,
+        wave: &Wave,
+        rng: &mut Rng,
+        clock: &Clock,
+    ) -> Option<Coordinate> {
+        if self.timer.elapsed(clock) < 1.0 / wave.fire_rate || in_flight >= wave.max_alien_bullets {
+            return None;
+        }
+        let loaded: Vec<usize> = front_line(aliens)
+            .into_iter()
+            .filter(|&a| aliens[a].is_loaded(wave.fire_cooldown, clock))
+            .collect();
+        let above_target: Vec<usize> = loaded
+            .iter()
+            .copied()
+            .filter(|&a| {
+                let alien = &aliens[a];
+                target
+                    .iter()
+                    .any(|x| (alien.far_left()..=alien.far_right()).contains(x))
+            })
+            .collect();
+        let candidates = match above_target.is_empty() || !rng.chance(wave.aim) {
+            true => &loaded,
+            false => &above_target,
+        };
+        let shooter = *rng.pick(candidates)?;
+        aliens[shooter].reload(clock);
+        self.timer.reset(clock);
+        Some(aliens[shooter].head())
+    }
+
+    /// Holds the aliens' fire for a full interval, e.g. when a wave begins
+    pub fn reset(&mut self, clock: &Clock) {
+        self.timer.reset(clock);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Returns a single row of aliens, three columns apart
+    fn row(count: usize) -> Vec<Alien> {
+        let engine = Rc::new(RefCell::new(Engine::new((30, 10))));
+        (0..count)
+            .map(|i| Alien::new(engine.clone(), vec![(i * 3, 2)], 1.0).unwrap())
+            .collect()
+    }
+
+    fn wave() -> Wave {
+        Wave {
+            fire_rate: 5.0,
+            fire_cooldown: 1.5,
+            max_alien_bullets: 3,
+            aim: 0.5,
+            ..Wave::first()
+        }
+    }
+
+    /// Fires every `step` seconds for the given seconds, returning the shots
+    fn shots(seed: u64, seconds: f32, step: f32) -> Vec<Option<Coordinate>> {
+        let (mut aliens, wave, mut rng) = (row(5), wave(), Rng::new(seed));
+        let mut clock = Clock::new();
+        let mut fire_control = FireControl::new(&clock);
+        let mut shots = Vec::new();
+        while clock.now() < seconds as f64 {
+            clock.advance(step);
+            shots.push(fire_control.fire(&mut aliens, &[6], 0, &wave, &mut rng, &clock));
+        }
+        shots
+    }
+
+    #[test]
+    fn never_fires_past_the_cap() {
+        let (mut aliens, wave, mut rng) = (row(5), wave(), Rng::new(1));
+        let mut clock = Clock::new();
+        let mut fire_control = FireControl::new(&clock);
+        for _ in 0..100 {
+            clock.advance(1.0);
+            let in_flight = wave.max_alien_bullets;
+            assert!(fire_control
+                .fire(&mut aliens, &[6], in_flight, &wave, &mut rng, &clock)
+                .is_none());
+        }
+        assert!(fire_control
+            .fire(&mut aliens, &[6], 2, &wave, &mut rng, &clock)
+            .is_some());
+    }
+
+    #[test]
+    fn fires_at_most_at_the_wave_rate() {
+        let shots = shots(1, 10.0, 0.05);
+        // five shots a second, one every four steps at best
+        assert!(shots.iter().flatten().count() <= 50);
+        for window in shots.windows(4) {
+            assert!(window.iter().flatten().count() <= 1);
+        }
+    }
+
+    #[test]
+    fn an_alien_that_fired_waits_for_its_cooldown() {
+        let (mut aliens, wave, mut rng) = (row(1), wave(), Rng::new(1));
+        let mut clock = Clock::new();
+        let mut fire_control = FireControl::new(&clock);
+        clock.advance(0.2);
+        assert!(fire_control
+            .fire(&mut aliens, &[6], 0, &wave, &mut rng, &clock)
+            .is_some());
+        clock.advance(1.0);
+        assert!(fire_control
+            .fire(&mut aliens, &[6], 0, &wave, &mut rng, &clock)
+            .is_none());
+        clock.advance(0.5);
+        assert!(fire_control
+            .fire(&mut aliens, &[6], 0, &wave, &mut rng, &clock)
+            .is_some());
+    }
+
+    #[test]
+    fn the_same_seed_fires_the_same_shots() {
+        let shots_of = |seed| shots(seed, 10.0, 0.05);
+        assert!(shots_of(7).iter().flatten().count() > 0);
+        assert_eq!(shots_of(7), shots_of(7));
+        assert_ne!(shots_of(7), shots_of(8));
+    }
+}
@@ -0,0 +1,827 @@
+//! Alien Formations
+//!
+//! What the aliens look like and how they line up, read from a data file so new
+//! alien types and waves can be designed without touching the code.
+//!
+//! # File Lookup
+//! - The path given with `--formations <file>`, which must exist.
+//! - Otherwise `formations.toml` in the current directory, if there is one.
+//! - Otherwise `formations.example.toml`, built into the game.
+//!
+//! # File Format
+//! ```toml
+//! [types.crab]
+//! shape = [
+//!     "#.#",
+//!     "###",
+//! ]
+//! hit_points = 2
+//!
+//! [types.squid]
+//! shape = [".#.", "#.#"]
+//! behaviour = "dive"
+//!
+//! [formation]
+//! rows = ["crab", "invader"]
+//! spacing = 0
+//! row_spacing = 4
+//! top = 4
+//!
+//! [wave.3]
+//! rows = ["crab", "crab"]
+//! columns = 4
+//! ```
+//!
+//! - `[types.<name>]`: an alien type, drawn as ASCII art where `#` is a pixel,
+//!   the hits it takes to destroy and its [`Behaviour`].
+//! - `[formation]`: the type of every row from the top down, the last one filling
+//!   in for rows past the list, and the spacing between aliens and rows.
+//! - `[wave.<number>]`: lays a single wave out by hand, overriding `[formation]`.
+//!
+//! How many rows and columns a wave has comes from [`crate::wave::Wave`], unless
+//! its own section says otherwise. Aliens that don't fit on the plane are left out.
+//!
+//! # Access
+//! Once loaded, the formations are installed with [`set()`] and read anywhere
+//! through [`get()`], which falls back to the built-in ones if nothing was installed.
+use crate::config::parser::{self, Document, Value};
+use crate::config::{invalid, reject_unknown_keys, take_count};
+use crate::engine::Coordinate;
+use crate::errors::{Error, ErrorKind};
+use crate::wave::Wave;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// File looked up in the current directory when no path is given
+pub const DEFAULT_FORMATIONS_PATH: &str = "formations.toml";
+/// Formations played when there is no file
+const BUILT_IN: &str = include_str!("../formations.example.toml");
+/// Fraction of the plane, from the top, the formation's rows have to start in
+const FORMATION_DEPTH: f32 = 0.6;
+
+static FORMATIONS: OnceLock<Formations> = OnceLock::new();
+
+/// Returns the installed formations, or the built-in ones if none were installed
+pub fn get() -> &'static Formations {
+    FORMATIONS.get_or_init(Formations::default)
+}
+
+/// Installs the formations read by [`get()`]. Only the first call has an effect.
+pub fn set(formations: Formations) {
+    let _ = FORMATIONS.set(formations);
+}
+
+/// What an alien does besides marching with the formation
+///
+/// Any alien with more than one hit point is armored on top of
+/// that, every hit that doesn't destroy it knocks a pixel off.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Behaviour {
+    /// stays in the formation
+    March,
+    /// breaks off from the formation now and then to swoop at the shooter
+    Dive,
+    /// splits into two aliens of the named type when destroyed
+    Split(String),
+}
+
+/// A kind of alien, its look and toughness
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlienType {
+    pub name: String,
+    /// pixels of the alien, relative to its top left corner
+    pub shape: Vec<Coordinate>,
+    pub width: usize,
+    pub height: usize,
+    /// hits it takes to destroy the alien
+    pub hit_points: usize,
+    pub behaviour: Behaviour,
+}
+
+impl AlienType {
+    /// Returns the alien's pixels with its top left corner at the given coordinate
+    pub fn at(&self, (x, y): Coordinate) -> Vec<Coordinate> {
+        self.shape.iter().map(|(dx, dy)| (x + dx, y + dy)).collect()
+    }
+}
+
+/// How the aliens of a wave line up
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    /// alien type of every row, from the top one down
+    pub rows: Vec<String>,
+    /// aliens in every row, `None` to take the wave's
+    pub columns: Option<usize>,
+    /// columns from the left of an alien to the left of the next, 0 to spread them evenly
+    pub spacing: usize,
+    /// rows from the top of a formation row to the top of the next
+    pub row_spacing: usize,
+    /// plane row the formation starts on
+    pub top: usize,
+    /// if true, `rows` is exactly the rows of the wave
+    pub exact_rows: bool,
+}
+
+/// An alien to be spawned
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    pub alien_type: AlienType,
+    /// formation row, 0 being the top one
+    pub row: usize,
+    /// the alien's pixels on the plane
+    pub position: Vec<Coordinate>,
+}
+
+/// Every alien type and how the waves line up
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formations {
+    types: BTreeMap<String, AlienType>,
+    formation: Layout,
+    /// layouts of the waves designed by hand, by wave number
+    waves: BTreeMap<usize, Layout>,
+}
+
+impl Default for Formations {
+    fn default() -> Self {
+        Self::parse(BUILT_IN).expect("The built-in formations are invalid")
+    }
+}
+
+impl Formations {
+    /// Loads the formations from the given path, or from `formations.toml` if
+    /// no path is given and the file exists, otherwise returns the built-in ones
+    pub fn load(path: Option<&str>) -> Result<Self, Error> {
+        match path {
+            Some(path) => Self::from_file(path),
+            None if Path::new(DEFAULT_FORMATIONS_PATH).exists() => {
+                Self::from_file(DEFAULT_FORMATIONS_PATH)
+            }
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Reads and validates a formations file
+    pub fn from_file(path: &str) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            Error::new(
+                ErrorKind::Io,
+                format!("Could not read formations `{}`: {}", path, e),
+            )
+        })?;
+        Self::parse(&text)
+            .map_err(|e| Error::new(e.kind(), format!("In `{}`, {}", path, e.diagnosis())))
+    }
+
+    /// Parses and validates the contents of a formations file
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut document = parser::parse(text)?;
+        let mut types = BTreeMap::new();
+        for name in section_names(&document, "types") {
+            let alien_type = take_type(&mut document, &name)?;
+            types.insert(name, alien_type);
+        }
+        validate_splits(&types)?;
+        let formation = take_layout(&mut document, "formation", None, &types)?;
+        let mut waves = BTreeMap::new();
+        for number in section_names(&document, "wave") {
+            let prefix = format!("wave.{}", number);
+            let wave = match number.parse::<usize>() {
+                Ok(wave) if wave > 0 => wave,
+                _ => {
+                    let line = document.keys().find(|(key, _)| key.starts_with(&prefix));
+                    return Err(invalid(
+                        &prefix,
+                        line.map_or(0, |(_, line)| line),
+                        "must be named after a wave number, starting at 1".to_string(),
+                    ));
+                }
+            };
+            let layout = take_layout(&mut document, &prefix, Some(&formation), &types)?;
+            waves.insert(wave, layout);
+        }
+        reject_unknown_keys(&document)?;
+        Ok(Self {
+            types,
+            formation,
+            waves,
+        })
+    }
+
+    /// Returns the alien type with the given name
+    pub fn alien_type(&self, name: &str) -> Option<&AlienType> {
+        self.types.get(name)
+    }
+
+    /// Returns how the given wave lines up
+    pub fn layout(&self, wave: usize) -> &Layout {
+        self.waves.get(&wave).unwrap_or(&self.formation)
+    }
+
+    /// Lays the aliens of a wave out on a plane of the given dimensions
+    pub fn place(&self, wave: &Wave, (width, height): Coordinate) -> Vec<Placement> {
+        let layout = self.layout(wave.number);
+        let rows = match layout.exact_rows {
+            true => layout.rows.len(),
+            false => wave.rows,
+        };
+        let columns = layout.columns.unwrap_or(wave.columns).max(1);
+        let deepest = (height as f32 * FORMATION_DEPTH) as usize;
+        let mut placements = Vec::new();
+        for row in 0..rows {
+            let y = layout.top + row * layout.row_spacing;
+            if row > 0 && y > deepest {
+                // the rest of the formation would be on top of the shooter
+                break;
+            }
+            // rows past the listed ones take the last type
+            let name = layout.rows.get(row).or(layout.rows.last());
+            let alien_type = match name.and_then(|name| self.alien_type(name)) {
+                Some(alien_type) => alien_type,
+                None => continue,
+            };
+            if y + alien_type.height > height {
+                break;
+            }
+            for x in row_columns(layout, alien_type, columns, width) {
+                placements.push(Placement {
+                    alien_type: alien_type.clone(),
+                    row,
+                    position: alien_type.at((x, y)),
+                });
+            }
+        }
+        placements
+    }
+}
+
+/// Returns the left column of every alien of a row
+fn row_columns(
+    layout: &Layout,
+    alien_type: &AlienType,
+    columns: usize,
+    width: usize,
+) -> Vec<usize> {
+    if layout.spacing == 0 {
+        // spreading the aliens evenly, each one starting every `delta`
+        // columns as long as it doesn't go over the plane's edge
+        let delta = (width / columns).max(alien_type.width + 1);
+        return (0..width)
+            .step_by(delta)
+            .filter(|x| x + alien_type.width + 1 < width)
+            .collect();
+    }
+    // centering the row, leaving out the aliens that don't fit
+    let fitting = (width.saturating_sub(alien_type.width) / layout.spacing + 1).min(columns);
+    let row_width = (fitting - 1) * layout.spacing + alien_type.width;
+    let left = width.saturating_sub(row_width) / 2;
+    (0..fitting).map(|i| left + i * layout.spacing).collect()
+}
+
+/// Returns the distinct names of the sections nested in the given one
+fn section_names(document: &Document, parent: &str) -> Vec<String> {
+    let prefix = format!("{}.", parent);
+    let mut names: Vec<String> = document
+        .keys()
+        .filter_map(|(key, _)| key.strip_prefix(&prefix))
+        // keys straight in the parent section are left to be reported as unknown
+        .filter_map(|rest| rest.split_once('.').map(|(name, _)| name.to_string()))
+        .collect();
+    names.dedup();
+    names
+}
+
+/// Reads an alien type from its `[types.<name>]` section
+fn take_type(document: &mut Document, name: &str) -> Result<AlienType, Error> {
+    let key = format!("types.{}.shape", name);
+    let (lines, line) = match document.take(&key) {
+        Some((Value::Array(lines), line)) => (lines, line),
+        Some((value, line)) => {
+            return Err(invalid(
+                &key,
+                line,
+                format!(
+                    "must be a list of strings, found {} `{}`",
+                    value.type_name(),
+                    value
+                ),
+            ))
+        }
+        None => {
+            return Err(Error::new(
+                ErrorKind::InvalidConfig,
+                format!("alien type `{}` has no `shape`", name),
+            ))
+        }
+    };
+    let mut shape = Vec::new();
+    let (mut width, mut height) = (0, 0);
+    for (y, value) in lines.iter().enumerate() {
+        let text = match value {
+            Value::String(text) => text,
+            value => {
+                return Err(invalid(
+                    &key,
+                    line,
+                    format!("must be a list of strings, found `{}`", value),
+                ))
+            }
+        };
+        for (x, c) in text.chars().enumerate() {
+            match c {
+                '#' => {
+                    shape.push((x, y));
+                    width = width.max(x + 1);
+                    height = y + 1;
+                }
+                '.' | ' ' => (),
+                c => {
+                    return Err(invalid(
+                        &key,
+                        line,
+                        format!("can only be drawn with `#`, `.` and spaces, found `{}`", c),
+                    ))
+                }
+            }
+        }
+    }
+    if shape.is_empty() {
+        return Err(invalid(&key, line, "needs at least one `#`".to_string()));
+    }
+    let mut hit_points = 1;
+    take_count(
+        document,
+        &format!("types.{}.hit_points", name),
+        1,
+        100,
+        &mut hit_points,
+    )?;
+    let behaviour = take_behaviour(document, name)?;
+    Ok(AlienType {
+        name: name.to_string(),
+        shape,
+        width,
+        height,
+        hit_points,
+        behaviour,
+    })
+}
+
+/// Reads the `behaviour` of an alien type, and what it `splits_into` if it splits
+fn take_behaviour(document: &mut Document, name: &str) -> Result<Behaviour, Error> {
+    let key = format!("types.{}.behaviour", name);
+    let splits_into = format!("types.{}.splits_into", name);
+    let behaviour = match document.take(&key) {
+        None => Behaviour::March,
+        Some((Value::String(behaviour), line)) => match behaviour.as_str() {
+            "march" => Behaviour::March,
+            "dive" => Behaviour::Dive,
+            "split" => match document.take(&splits_into) {
+                Some((Value::String(into), _)) => return Ok(Behaviour::Split(into)),
+                Some((value, line)) => {
+                    return Err(invalid(
+                        &splits_into,
+                        line,
+                        format!("must be an alien type, found `{}`", value),
+                    ))
+                }
+                None => {
+                    return Err(invalid(
+                        &key,
+                        line,
+                        format!("is `split`, but `{}` is missing", splits_into),
+                    ))
+                }
+            },
+            other => {
+                return Err(invalid(
+                    &key,
+                    line,
+                    format!(
+                        "must be one of `march`, `dive` or `split`, found `{}`",
+                        other
+                    ),
+                ))
+            }
+        },
+        Some((value, line)) => {
+            return Err(invalid(
+                &key,
+                line,
+                format!("must be a string, found {} `{}`", value.type_name(), value),
+            ))
+        }
+    };
+    if let Some((_, line)) = document.take(&splits_into) {
+        return Err(invalid(
+            &splits_into,
+            line,
+            "only applies to aliens whose `behaviour` is `split`".to_string(),
+        ));
+    }
+    Ok(behaviour)
+}
+
+/// Makes sure splitting aliens split into known types that don't split
+/// themselves, otherwise a single alien could fill the plane
+fn validate_splits(types: &BTreeMap<String, AlienType>) -> Result<(), Error> {
+    for alien_type in types.values() {
+        let into = match &alien_type.behaviour {
+            Behaviour::Split(into) => into,
+            _ => continue,
+        };
+        let message = match types.get(into) {
+            None => format!("names the unknown alien type `{}`", into),
+            Some(other) if matches!(other.behaviour, Behaviour::Split(_)) => {
+                format!("names `{}`, which splits too", into)
+            }
+            Some(_) => continue,
+        };
+        return Err(Error::new(
+            ErrorKind::InvalidConfig,
+            format!("`types.{}.splits_into` {}", alien_type.name, message),
+        ));
+    }
+    Ok(())
+}
+
+/// Reads a layout from the given section, keys left out are taken from
+/// `base`, or are required if there is none
+fn take_layout(
+    document: &mut Document,
+    section: &str,
+    base: Option<&Layout>,
+    types: &BTreeMap<String, AlienType>,
+) -> Result<Layout, Error> {
+    let key = format!("{}.rows", section);
+    let rows = match document.take(&key) {
+        Some((Value::Array(values), line)) => {
+            let mut rows = Vec::new();
+            for value in values {
+                match value {
+                    Value::String(name) if types.contains_key(&name) => rows.push(name),
+                    Value::String(name) => {
+                        return Err(invalid(
+                            &key,
+                            line,
+                            format!("names the unknown alien type `{}`", name),
+                        ))
+                    }
+                    value => {
+                        return Err(invalid(
+                            &key,
+                            line,
+                            format!("must be a list of alien types, found `{}`", value),
+                        ))
+                    }
+                }
+            }
+            if rows.is_empty() {
+                return Err(invalid(&key, line, "can't be empty".to_string()));
+            }
+            Some((rows, line))
+        }
+        Some((value, line)) => {
+            return Err(invalid(
+                &key,
+                line,
+                format!(
+                    "must be a list of alien types, found {} `{}`",
+                    value.type_name(),
+                    value
+                ),
+            ))
+        }
+        None => None,
+    };
+    let mut layout = match (base, rows) {
+        (Some(base), rows) => Layout {
+            exact_rows: rows.is_some(),
+            rows: rows.map_or(base.rows.clone(), |(rows, _)| rows),
+            ..base.clone()
+        },
+        (None, Some((rows, _))) => Layout {
+            rows,
+            columns: None,
+            spacing: 0,
+            row_spacing: 4,
+            top: 4,
+            exact_rows: false,
+        },
+        (None, None) => {
+            return Err(Error::new(
+                ErrorKind::InvalidConfig,
+                format!("`[{}]` has no `rows`", section),
+            ))
+        }
+    };
+    let mut columns = 0;
+    take_count(
+        document,
+        &format!("{}.columns", section),
+        1,
+        100,
+        &mut columns,
+    )?;
+    if columns > 0 {
+        layout.columns = Some(columns);
+    }
+    // aliens touching each other would run into one another when moving
+    let widest = layout.rows.iter().map(|name| types[name].width).max();
+    let tallest = layout.rows.iter().map(|name| types[name].height).max();
+    take_count(
+        document,
+        &format!("{}.spacing", section),
+        0,
+        1000,
+        &mut layout.spacing,
+    )?;
+    if layout.spacing != 0 && layout.spacing <= widest.unwrap_or(0) {
+        return Err(Error::new(
+            ErrorKind::InvalidConfig,
+            format!(
+                "`{}.spacing` must be 0 or wider than the widest alien, {} columns",
+                section,
+                widest.unwrap_or(0)
+            ),
+        ));
+    }
+    take_count(
+        document,
+        &format!("{}.row_spacing", section),
+        1,
+        1000,
+        &mut layout.row_spacing,
+    )?;
+    if layout.row_spacing <= tallest.unwrap_or(0) {
+        return Err(Error::new(
+            ErrorKind::InvalidConfig,
+            format!(
+                "`{}.row_spacing` must be taller than the tallest alien, {} rows",
+                section,
+                tallest.unwrap_or(0)
+            ),
+        ));
+    }
+    take_count(
+        document,
+        &format!("{}.top", section),
+        0,
+        1000,
+        &mut layout.top,
+    )?;
+    Ok(layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INVADERS: &str =
+        "[types.invader]\nshape = [\"###\", \"#.#\"]\n\n[formation]\nrows = [\"invader\"]\n";
+
+    /// Parses formations expected to be rejected, returning the diagnosis
+    fn rejected(text: &str) -> String {
+        let error = Formations::parse(text).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidConfig);
+        error.diagnosis()
+    }
+
+    /// Returns the top left corner of every placed alien, by formation row
+    fn corners(placements: &[Placement]) -> Vec<(usize, Coordinate)> {
+        placements
+            .iter()
+            .map(|placement| (placement.row, placement.position[0]))
+            .collect()
+    }
+
+    fn wave(number: usize, rows: usize, columns: usize) -> Wave {
+        Wave {
+            number,
+            rows,
+            columns,
+            ..Wave::first()
+        }
+    }
+
+    #[test]
+    fn built_in_formations_are_valid() {
+        let formations = Formations::default();
+        assert!(formations.alien_type("invader").is_some());
+        assert!(!formations.place(&Wave::first(), (100, 25)).is_empty());
+    }
+
+    #[test]
+    fn shapes_are_drawn_as_ascii_art() {
+        let formations = Formations::parse(
+            "[types.crab]\nshape = [\n  \"#.#\",\n  \" # \",\n]\nhit_points = 2\n\n[formation]\nrows = [\"crab\"]",
+        )
+        .unwrap();
+        let crab = formations.alien_type("crab").unwrap();
+        assert_eq!(crab.shape, vec![(0, 0), (2, 0), (1, 1)]);
+        assert_eq!((crab.width, crab.height), (3, 2));
+        assert_eq!(crab.hit_points, 2);
+        assert_eq!(crab.behaviour, Behaviour::March);
+        assert_eq!(crab.at((10, 4)), vec![(10, 4), (12, 4), (11, 5)]);
+    }
+
+    #[test]
+    fn bad_types_are_rejected() {
+        assert_eq!(
+            rejected("[types.a]\nhit_points = 1\n[formation]\nrows = [\"a\"]"),
+            "alien type `a` has no `shape`"
+        );
+        assert_eq!(
+            rejected("[types.a]\nshape = [\"..\"]\n[formation]\nrows = [\"a\"]"),
+            "line 2: `types.a.shape` needs at least one `#`"
+        );
+        assert_eq!(
+            rejected("[types.a]\nshape = [\"#x\"]\n[formation]\nrows = [\"a\"]"),
+            "line 2: `types.a.shape` can only be drawn with `#`, `.` and spaces, found `x`"
+        );
+        assert_eq!(
+            rejected("[types.a]\nshape = \"#\"\n[formation]\nrows = [\"a\"]"),
+            "line 2: `types.a.shape` must be a list of strings, found a string `\"#\"`"
+        );
+        assert_eq!(
+            rejected("[types.a]\nshape = [\"#\"]\nhit_points = 0\n[formation]\nrows = [\"a\"]"),
+            "line 3: `types.a.hit_points` must be between 1 and 100, found 0"
+        );
+    }
+
+    #[test]
+    fn bad_layouts_are_rejected() {
+        assert_eq!(
+            rejected("[types.a]\nshape = [\"#\"]"),
+            "`[formation]` has no `rows`"
+        );
+        assert_eq!(
+            rejected("[types.a]\nshape = [\"#\"]\n[formation]\nrows = [\"b\"]"),
+            "line 4: `formation.rows` names the unknown alien type `b`"
+        );
+        assert_eq!(
+            rejected("[types.a]\nshape = [\"#\"]\n[formation]\nrows = []"),
+            "line 4: `formation.rows` can't be empty"
+        );
+        assert_eq!(
+            rejected(&format!("{}spacing = 3", INVADERS)),
+            "`formation.spacing` must be 0 or wider than the widest alien, 3 columns"
+        );
+        assert_eq!(
+            rejected(&format!("{}row_spacing = 2", INVADERS)),
+            "`formation.row_spacing` must be taller than the tallest alien, 2 rows"
+        );
+        assert_eq!(
+            rejected(&format!("{}[wave.0]\ncolumns = 2", INVADERS)),
+            "line 7: `wave.0` must be named after a wave number, starting at 1"
+        );
+        assert_eq!(
+            rejected(&format!("{}colour = 1", INVADERS)),
+            "line 6: unknown key `formation.colour`"
+        );
+    }
+
+    #[test]
+    fn aliens_spread_evenly_across_the_plane() {
+        let formations = Formations::parse(INVADERS).unwrap();
+        let placements = formations.place(&wave(1, 2, 6), (60, 24));
+        let expected: Vec<(usize, Coordinate)> = (0..2)
+            .flat_map(|row| (0..6).map(move |column| (row, (column * 10, 4 + row * 4))))
+            .collect();
+        assert_eq!(corners(&placements), expected);
+        assert_eq!(
+            placements[0].position,
+            vec![(0, 4), (1, 4), (2, 4), (0, 5), (2, 5)]
+        );
+    }
+
+    #[test]
+    fn spaced_rows_are_centered() {
+        let formations = Formations::parse(&format!("{}spacing = 12", INVADERS)).unwrap();
+        let placements = formations.place(&wave(1, 1, 4), (60, 24));
+        assert_eq!(
+            corners(&placements),
+            vec![(0, (10, 4)), (0, (22, 4)), (0, (34, 4)), (0, (46, 4))]
+        );
+        // only as many aliens as fit on the plane
+        assert_eq!(formations.place(&wave(1, 1, 10), (40, 24)).len(), 4);
+    }
+
+    #[test]
+    fn rows_past_the_listed_ones_take_the_last_type() {
+        let formations = Formations::parse(
+            "[types.a]\nshape = [\"#\"]\n[types.b]\nshape = [\"##\"]\n[formation]\nrows = [\"a\", \"b\"]\nrow_spacing = 2",
+        )
+        .unwrap();
+        let rows: Vec<(usize, String)> = formations
+            .place(&wave(1, 3, 1), (60, 24))
+            .into_iter()
+            .map(|placement| (placement.row, placement.alien_type.name))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (0, "a".to_string()),
+                (1, "b".to_string()),
+                (2, "b".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn rows_stop_short_of_the_shooter() {
+        let formations = Formations::parse(INVADERS).unwrap();
+        // rows start on 4, 8, 12 and 16, past 60% of the plane's 24 rows
+        let rows: Vec<usize> = formations
+            .place(&wave(1, 6, 1), (60, 24))
+            .iter()
+            .map(|placement| placement.row)
+            .collect();
+        assert_eq!(rows, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn waves_laid_out_by_hand_replace_the_formation() {
+        let formations = Formations::parse(&format!(
+            "{}[types.b]\nshape = [\"#\"]\n\n[wave.2]\nrows = [\"b\"]\ncolumns = 2\ntop = 6",
+            INVADERS
+        ))
+        .unwrap();
+        assert_eq!(formations.layout(1).rows, vec!["invader".to_string()]);
+        let layout = formations.layout(2);
+        assert_eq!(layout.rows, vec!["b".to_string()]);
+        assert_eq!(layout.columns, Some(2));
+        assert!(layout.exact_rows);
+        // exactly the listed rows, whatever the wave asks for
+        let placements = formations.place(&wave(2, 3, 6), (60, 24));
+        assert_eq!(corners(&placements), vec![(0, (0, 6)), (0, (30, 6))]);
+    }
+
+    #[test]
+    fn behaviours_are_read_by_name() {
+        let formations = Formations::parse(&format!(
+            "{}[types.diver]\nshape = [\"#\"]\nbehaviour = \"dive\"\n[types.splitter]\nshape = [\"#\"]\nbehaviour = \"split\"\nsplits_into = \"invader\"",
+            INVADERS
+        ))
+        .unwrap();
+        assert_eq!(
+            formations.alien_type("invader").unwrap().behaviour,
+            Behaviour::March
+        );
+        assert_eq!(
+            formations.alien_type("diver").unwrap().behaviour,
+            Behaviour::Dive
+        );
+        assert_eq!(
+            formations.alien_type("splitter").unwrap().behaviour,
+            Behaviour::Split("invader".to_string())
+        );
+        assert_eq!(
+            rejected(&format!(
+                "{}[types.a]\nshape = [\"#\"]\nbehaviour = \"fly\"",
+                INVADERS
+            )),
+            "line 8: `types.a.behaviour` must be one of `march`, `dive` or `split`, found `fly`"
+        );
+        assert_eq!(
+            rejected(&format!(
+                "{}[types.a]\nshape = [\"#\"]\nbehaviour = \"split\"",
+                INVADERS
+            )),
+            "line 8: `types.a.behaviour` is `split`, but `types.a.splits_into` is missing"
+        );
+        assert_eq!(
+            rejected(&format!(
+                "{}[types.a]\nshape = [\"#\"]\nsplits_into = \"invader\"",
+                INVADERS
+            )),
+            "line 8: `types.a.splits_into` only applies to aliens whose `behaviour` is `split`"
+        );
+    }
+
+    #[test]
+    fn splits_must_name_a_known_type_that_does_not_split() {
+        assert_eq!(
+            rejected(&format!(
+                "{}[types.a]\nshape = [\"#\"]\nbehaviour = \"split\"\nsplits_into = \"ghost\"",
+                INVADERS
+            )),
+            "`types.a.splits_into` names the unknown alien type `ghost`"
+        );
+        assert_eq!(
+            rejected(&format!(
+                "{}[types.a]\nshape = [\"#\"]\nbehaviour = \"split\"\nsplits_into = \"a\"",
+                INVADERS
+            )),
+            "`types.a.splits_into` names `a`, which splits too"
+        );
+        assert_eq!(
+            rejected(&format!(
+                "{}[types.a]\nshape = [\"#\"]\nbehaviour = \"split\"\nsplits_into = \"b\"\n[types.b]\nshape = [\"#\"]\nbehaviour = \"split\"\nsplits_into = \"invader\"",
+                INVADERS
+            )),
+            "`types.a.splits_into` names `b`, which splits too"
+        );
+    }
+}
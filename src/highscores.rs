@@ -0,0 +1,344 @@
+//! High Scores
+//!
+//! Keeps the best games across runs in a plain text file under the user's data
+//! directory (see [`utils::data_dir()`]). Every entry records who played, the score,
+//! the wave reached, how long the game lasted and the day it was played on.
+//!
+//! A missing or unreadable file is never an error, the table simply starts out
+//! empty. A corrupted one is moved aside to `highscores.bak` first, so saving
+//! the new table doesn't overwrite it. Only the best [`MAX_ENTRIES`] games are kept.
+//!
+//! # File Format
+//! ```text
+//! # spaceinvaders highscores v1
+//! 1230	1	125.5	1760745600	alice
+//! 870	1	98.25	1760659200	bob
+//! ```
+//!
+//! Every line holds the score, the wave reached, the seconds played, the Unix
+//! timestamp of the game and the player's name, separated by tabs.
+//!
+//! # Example
+//! ```rust
+//! use crate::highscores::{HighScore, HighScores};
+//!
+//! let mut table = HighScores::load_default();
+//! if table.qualifies(game.score()) {
+//!     table.insert(HighScore::new("alice", game.score(), game.wave(), game.play_time()));
+//!     table.save()?;
+//! }
+//! ```
+use crate::errors::{Error, ErrorKind};
+use crate::utils;
+
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HEADER: &str = "# spaceinvaders highscores v1";
+const FILE_NAME: &str = "highscores.txt";
+
+/// Games kept in the table
+pub const MAX_ENTRIES: usize = 10;
+/// Longest name a player can enter
+pub const MAX_NAME_LEN: usize = 12;
+/// Name recorded when the player doesn't enter one
+const DEFAULT_NAME: &str = "PLAYER";
+
+/// A single game in the table
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u64,
+    /// wave the game ended on, starting at 1
+    pub wave: usize,
+    /// simulated seconds the game lasted, pauses excluded
+    pub seconds: f64,
+    /// seconds since the Unix epoch the game was recorded at
+    pub timestamp: u64,
+}
+
+impl HighScore {
+    /// Records a game played right now, the name is cut
+    /// short and stripped of anything that isn't printable
+    pub fn new(name: &str, score: u64, wave: usize, seconds: f64) -> Self {
+        let name: String = name
+            .chars()
+            .filter(|c| !c.is_control())
+            .take(MAX_NAME_LEN)
+            .collect();
+        let name = match name.trim() {
+            "" => DEFAULT_NAME.to_string(),
+            trimmed => trimmed.to_string(),
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self {
+            name,
+            score,
+            wave,
+            seconds,
+            timestamp,
+        }
+    }
+
+    /// Returns the day the game was played on, as `YYYY-MM-DD`
+    pub fn date(&self) -> String {
+        utils::format_date(self.timestamp)
+    }
+
+    /// Converts the entry into a line of the high-score file
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}",
+            self.score, self.wave, self.seconds, self.timestamp, self.name
+        )
+    }
+
+    /// Parses a line of the high-score file
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(5, '\t');
+        let entry = Self {
+            score: fields.next()?.parse().ok()?,
+            wave: fields.next()?.parse().ok()?,
+            seconds: fields.next()?.parse().ok()?,
+            timestamp: fields.next()?.parse().ok()?,
+            name: fields.next()?.to_string(),
+        };
+        if entry.name.is_empty() || entry.name.chars().any(|c| c.is_control()) {
+            return None;
+        }
+        Some(entry)
+    }
+}
+
+/// The best games played, highest score first
+#[derive(Debug, Clone, Default)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+    /// file the table is saved to, `None` if there is nowhere to save it
+    path: Option<PathBuf>,
+}
+
+impl HighScores {
+    /// Initializer function for an empty table that isn't saved anywhere
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the table at the given path, falling back to an empty one if the
+    /// file is missing or corrupted. A corrupted file is kept as a backup
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => parse(&contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Some(Vec::new()),
+            Err(_) => None,
+        };
+        let entries = entries.unwrap_or_else(|| {
+            let _ = fs::rename(&path, path.with_extension("bak"));
+            Vec::new()
+        });
+        let mut table = Self {
+            entries,
+            path: Some(path),
+        };
+        table.sort();
+        table
+    }
+
+    /// Reads the table kept in the user's data directory
+    pub fn load_default() -> Self {
+        match utils::data_dir() {
+            Some(dir) => Self::load(dir.join(FILE_NAME)),
+            None => Self::new(),
+        }
+    }
+
+    /// Returns true if a game with the given score would make it into the table
+    pub fn qualifies(&self, score: u64) -> bool {
+        if score == 0 {
+            return false;
+        }
+        self.entries.len() < MAX_ENTRIES || self.entries.iter().any(|entry| score > entry.score)
+    }
+
+    /// Adds a game to the table, returning its rank starting
+    /// at 1, or `None` if it didn't make the cut
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        // games tied on score are ranked by who got there first
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank + 1)
+    }
+
+    /// Writes the table to its file, creating the data directory if needed
+    pub fn save(&self) -> Result<(), Error> {
+        let path = self.path.as_ref().ok_or_else(|| {
+            Error::new(
+                ErrorKind::Io,
+                "There is no data directory to save the high scores to".to_string(),
+            )
+        })?;
+        let io_error = |e: std::io::Error| {
+            Error::new(
+                ErrorKind::Io,
+                format!(
+                    "Could not save the high scores to `{}`: {}",
+                    path.display(),
+                    e
+                ),
+            )
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        let mut contents = format!("{}\n", HEADER);
+        for entry in self.entries.iter() {
+            contents.push_str(&entry.to_line());
+            contents.push('\n');
+        }
+        // writing next to the table first, so a crash midway
+        // never leaves a half written file behind
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, contents).map_err(io_error)?;
+        fs::rename(&temporary, path).map_err(io_error)
+    }
+
+    /// Returns the table laid out in columns, one line per game
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{:>2}  {:<width$}  {:>7}  {:>4}  {:>6}  {}",
+            "#",
+            "NAME",
+            "SCORE",
+            "WAVE",
+            "TIME",
+            "DATE",
+            width = MAX_NAME_LEN
+        )];
+        if self.entries.is_empty() {
+            lines.push("No high scores yet, go set one!".to_string());
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            let seconds = entry.seconds as u64;
+            lines.push(format!(
+                "{:>2}  {:<width$}  {:>7}  {:>4}  {:>6}  {}",
+                i + 1,
+                entry.name,
+                entry.score,
+                entry.wave,
+                format!("{}:{:02}", seconds / 60, seconds % 60),
+                entry.date(),
+                width = MAX_NAME_LEN
+            ));
+        }
+        lines
+    }
+
+    /// Keeps the best games first, and only as many as fit
+    fn sort(&mut self) {
+        // stable, so ties keep the order they were saved in
+        self.entries.sort_by_key(|entry| Reverse(entry.score));
+        self.entries.truncate(MAX_ENTRIES);
+    }
+}
+
+/// Parses a whole high-score file, `None` if any of it is corrupted
+fn parse(contents: &str) -> Option<Vec<HighScore>> {
+    let mut lines = contents.lines();
+    if lines.next()? != HEADER {
+        return None;
+    }
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .map(HighScore::from_line)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a path in the temporary directory, unique to the test
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("spaceinvaders-{}.txt", name))
+    }
+
+    fn entry(name: &str, score: u64) -> HighScore {
+        HighScore::new(name, score, 1, 60.0)
+    }
+
+    /// Loads a table from a file holding the contents, returning it
+    /// along with whatever was kept as a backup
+    fn load(name: &str, contents: &str) -> (HighScores, Option<String>) {
+        let path = temp_path(name);
+        fs::write(&path, contents).unwrap();
+        let table = HighScores::load(&path);
+        let backup = fs::read_to_string(path.with_extension("bak")).ok();
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(path.with_extension("bak"));
+        (table, backup)
+    }
+
+    #[test]
+    fn a_missing_file_is_an_empty_table() {
+        let table = HighScores::load(temp_path("missing"));
+        assert!(table.entries.is_empty());
+    }
+
+    #[test]
+    fn saved_games_load_back_in_order() {
+        let path = temp_path("round-trip");
+        let mut table = HighScores::load(&path);
+        table.insert(entry("bob", 870));
+        table.insert(entry("alice", 1230));
+        table.save().unwrap();
+        let loaded = HighScores::load(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded.entries, [entry("alice", 1230), entry("bob", 870)]);
+    }
+
+    #[test]
+    fn a_bad_header_is_an_empty_table_and_a_backup() {
+        let contents = "# someone else's file\n1230\t1\t125.5\t1760745600\talice\n";
+        let (table, backup) = load("bad-header", contents);
+        assert!(table.entries.is_empty());
+        assert_eq!(backup.as_deref(), Some(contents));
+    }
+
+    #[test]
+    fn a_malformed_line_is_an_empty_table_and_a_backup() {
+        let contents = format!("{}\n1230\t1\t125.5\t1760745600\talice\n870\tone\n", HEADER);
+        let (table, backup) = load("malformed-line", &contents);
+        assert!(table.entries.is_empty());
+        assert_eq!(backup, Some(contents));
+    }
+
+    #[test]
+    fn inserting_ranks_the_game_and_keeps_the_best() {
+        let mut table = HighScores::new();
+        for score in (1..=MAX_ENTRIES as u64).map(|n| n * 100) {
+            table.insert(entry("alice", score));
+        }
+        assert_eq!(table.insert(entry("bob", 550)), Some(6));
+        // ties go to whoever got there first
+        assert_eq!(table.insert(entry("carol", 550)), Some(7));
+        assert_eq!(table.entries.len(), MAX_ENTRIES);
+        assert_eq!(table.entries.last().unwrap().score, 300);
+        assert!(!table.qualifies(300));
+        assert_eq!(table.insert(entry("dave", 50)), None);
+        assert_eq!(table.insert(entry("erin", 2000)), Some(1));
+    }
+}
@@ -0,0 +1,288 @@
+//! Input Actions and Key Bindings
+//!
+//! The game never looks at raw keys. The listener translates every key into an
+//! [`Action`] through a [`KeyBindings`] table, and only the action reaches the
+//! game, a recording or a replay. That way movement can be bound to WASD or vim
+//! keys, or firing to more than one key, without touching the game logic.
+//!
+//! Every frame, the listener reports all the actions whose keys are down as an
+//! [`InputState`], so several can be taken at once, e.g. moving while firing.
+//!
+//! # Key Names
+//! Keys are written by name in the configuration:
+//!
+//! |Name|Key|
+//! |-|-|
+//! |`left`, `right`, `up`, `down`|Arrow keys|
+//! |`space`, `enter`, `tab`, `esc`|Their respective keys|
+//! |`a`...`z`, `0`...`9`|Letters and digits, case insensitive|
+//!
+//! # Example
+//! ```rust
+//! use crate::input::{Action, Key, KeyBindings};
+//!
+//! let mut bindings = KeyBindings::default();
+//! bindings.bind(Action::MoveLeft, vec![Key::Left, Key::Char('a'), Key::Char('h')])?;
+//! assert!(bindings.conflict().is_none());
+//! assert_eq!(bindings.keys_for(Action::MoveLeft), [Key::Left, Key::Char('a'), Key::Char('h')]);
+//! ```
+use crate::errors::{Error, ErrorKind};
+
+use std::fmt;
+
+/// Everything the player can ask the game to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Pause,
+    Quit,
+    /// shows or hides the high-score table on the title screen
+    HighScores,
+}
+
+impl Action {
+    /// Every action, in the order they're checked when several keys are held
+    pub const ALL: [Action; 6] = [
+        Action::Fire,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Quit,
+        Action::Pause,
+        Action::HighScores,
+    ];
+
+    /// Name of the action, as written in the configuration and replays
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Fire => "fire",
+            Action::Pause => "pause",
+            Action::Quit => "quit",
+            Action::HighScores => "high_scores",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A physical key, independent of the platform it was read on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Left,
+    Right,
+    Up,
+    Down,
+    Space,
+    Enter,
+    Tab,
+    Esc,
+    /// a lowercase ASCII letter or a digit
+    Char(char),
+}
+
+impl Key {
+    /// Parses a key name, see the module documentation for the names accepted
+    pub fn from_name(name: &str) -> Option<Key> {
+        let key = match name.to_ascii_lowercase().as_str() {
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "space" => Key::Space,
+            "enter" => Key::Enter,
+            "tab" => Key::Tab,
+            "esc" => Key::Esc,
+            other => {
+                let mut chars = other.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Key::from_char(c)?,
+                    _ => return None,
+                }
+            }
+        };
+        Some(key)
+    }
+
+    /// Returns the key for a typed character, letters are case insensitive
+    pub fn from_char(c: char) -> Option<Key> {
+        match c {
+            ' ' => Some(Key::Space),
+            c if c.is_ascii_alphanumeric() => Some(Key::Char(c.to_ascii_lowercase())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Left => write!(f, "left"),
+            Key::Right => write!(f, "right"),
+            Key::Up => write!(f, "up"),
+            Key::Down => write!(f, "down"),
+            Key::Space => write!(f, "space"),
+            Key::Enter => write!(f, "enter"),
+            Key::Tab => write!(f, "tab"),
+            Key::Esc => write!(f, "esc"),
+            Key::Char(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+/// Maps keys to actions, every action can be bound to several keys
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Default for KeyBindings {
+    /// The arrow keys to move, space to fire, `p` to pause, escape to quit
+    /// and `h` for the high scores
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                (Action::Fire, vec![Key::Space]),
+                (Action::MoveLeft, vec![Key::Left]),
+                (Action::MoveRight, vec![Key::Right]),
+                (Action::Quit, vec![Key::Esc]),
+                (Action::Pause, vec![Key::Char('p')]),
+                (Action::HighScores, vec![Key::Char('h')]),
+            ],
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Replaces the keys bound to an action, fails if no key is given.
+    ///
+    /// Keys aren't checked against the other actions, so two actions can swap keys
+    /// one at a time. Use [`KeyBindings::conflict()`] once every action is bound.
+    pub fn bind(&mut self, action: Action, keys: Vec<Key>) -> Result<(), Error> {
+        if keys.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidArgument,
+                format!("`{}` needs at least one key", action),
+            ));
+        }
+        for (bound, bound_keys) in self.bindings.iter_mut() {
+            if *bound == action {
+                *bound_keys = keys;
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a key bound to two different actions, if there is one
+    pub fn conflict(&self) -> Option<(Key, Action, Action)> {
+        let bound: Vec<(Key, Action)> = self.iter().collect();
+        for (i, (key, action)) in bound.iter().enumerate() {
+            for (other_key, other) in &bound[i + 1..] {
+                if key == other_key && action != other {
+                    return Some((*key, *action, *other));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the keys bound to an action
+    pub fn keys_for(&self, action: Action) -> &[Key] {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Iterates over every bound key and its action, in [`Action::ALL`] order
+    pub fn iter(&self) -> impl Iterator<Item = (Key, Action)> + '_ {
+        self.bindings
+            .iter()
+            .flat_map(|(action, keys)| keys.iter().map(move |key| (*key, *action)))
+    }
+}
+
+/// The actions asked for on a single frame
+///
+/// An action is *held* for as long as one of its keys is down, and *pressed* on
+/// the frame its key goes down, or repeats. Movement follows the held actions
+/// so the shooter keeps moving while firing, everything else the pressed ones.
+#[derive(Debug, Clone, Default)]
+pub struct InputState {
+    held: Vec<Action>,
+    pressed: Vec<Action>,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks an action as held
+    pub fn hold(&mut self, action: Action) {
+        if !self.held.contains(&action) {
+            self.held.push(action);
+        }
+    }
+
+    /// Marks an action as pressed, which also holds it
+    pub fn press(&mut self, action: Action) {
+        self.hold(action);
+        if !self.pressed.contains(&action) {
+            self.pressed.push(action);
+        }
+    }
+
+    pub fn is_held(&self, action: Action) -> bool {
+        self.held.contains(&action)
+    }
+
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Returns the held actions, in [`Action::ALL`] order
+    pub fn held(&self) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|action| self.is_held(*action))
+            .collect()
+    }
+
+    /// Returns the pressed actions, in [`Action::ALL`] order
+    pub fn pressed(&self) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|action| self.is_pressed(*action))
+            .collect()
+    }
+
+    /// Returns true if nothing was pressed on this frame
+    pub fn nothing_pressed(&self) -> bool {
+        self.pressed.is_empty()
+    }
+
+    /// Forgets the pressed actions, keeping the held ones
+    pub fn clear_pressed(&mut self) {
+        self.pressed.clear();
+    }
+}
+
+impl PartialEq for InputState {
+    /// The order actions were held or pressed in doesn't matter
+    fn eq(&self, other: &Self) -> bool {
+        self.held() == other.held() && self.pressed() == other.pressed()
+    }
+}
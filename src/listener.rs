@@ -15,34 +15,45 @@
 //!   - **Windows**: Uses `GetAsyncKeyState` from the WinAPI for instantaneous, low-latency input polling.
 //!     This allows for **smooth key stream infiltration**, meaning that holding a key down yields a
 //!     consistent and uninterrupted input stream—ideal for fast-paced or continuous input scenarios.
-//!   - **Unix-like systems**: Uses `crossterm` to poll and read `KeyEvent` events, filtering for key *presses* only.
+//!   - **Unix-like systems**: Uses `crossterm` to read every pending `KeyEvent`. Terminals supporting
+//!     the keyboard enhancement protocol (e.g. kitty, WezTerm, foot) are asked to report key releases,
+//!     so held keys are tracked exactly. Elsewhere a key only counts as held on the frames it's
+//!     pressed or repeated on.
 //!
-//! - Detects common game-relevant keys:
-//!   - Arrow keys: `"left"`, `"right"`
-//!   - Spacebar: `" "`
-//!   - Escape: `"esc"`
-//!   - Pause: `"p"`
+//! - Every key down is reported each frame, as an [`InputState`], so the shooter can move and fire
+//!   at the same time.
+//!
+//! - Keys are translated into an [`Action`] through the [`KeyBindings`] given, keys
+//!   that aren't bound to anything are ignored. By default:
+//!   - Arrow keys: [`Action::MoveLeft`], [`Action::MoveRight`]
+//!   - Spacebar: [`Action::Fire`]
+//!   - Escape: [`Action::Quit`]
+//!   - `p`: [`Action::Pause`]
+//!   - `h`: [`Action::HighScores`]
 //!
 //! # Usage Example
 //!
 //! ```rust
-//! use crate::listener::get_key;
+//! use crate::config;
+//! use crate::input::Action;
+//! use crate::listener::Listener;
 //!
-//! if let Some(key) = get_key() {
-//!     match key.as_str() {
-//!         "left" => println!("Move left"),
-//!         "right" => println!("Move right"),
-//!         " " => println!("Jump or shoot"),
-//!         "p" => println!("Pause"),
-//!         "esc" => println!("Quit"),
-//!         _ => {}
-//!     }
+//! let mut listener = Listener::new();
+//! listener.enable();
+//! let input = listener.poll(&config::get().keys);
+//! if input.is_held(Action::MoveLeft) {
+//!     println!("Moving left");
+//! }
+//! if input.is_pressed(Action::Fire) {
+//!     println!("Shoot");
 //! }
+//! listener.disable();
 //! ```
 //!
 //! # Notes
 //!
-//! - On non-Windows systems, only key *presses* are returned. Key *releases* are ignored.
+//! - Without the keyboard enhancement protocol, terminals only repeat the last key pressed, so
+//!   holding a second key interrupts the first one.
 //! - Windows users benefit from smooth and continuous detection of held keys, enhancing
 //!   player responsiveness and interaction quality.
 //!
@@ -51,56 +62,147 @@
 //! - Windows implementation uses unsafe FFI (`winapi`) to directly access virtual key states.
 //! - Non-Windows implementation depends on the event polling behavior of `crossterm`.
 use crate::errors::{Error, ErrorKind};
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
-use std::io::Result;
+use crate::input::{InputState, Key, KeyBindings};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::{execute, terminal};
+use std::io::stdout;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+#[cfg(target_os = "windows")]
 use winapi::um::winuser::{
-    GetAsyncKeyState, VK_DOWN, VK_ESCAPE, VK_LEFT, VK_RIGHT, VK_SPACE, VK_UP,
+    GetAsyncKeyState, VK_DOWN, VK_ESCAPE, VK_LEFT, VK_RETURN, VK_RIGHT, VK_SPACE, VK_TAB, VK_UP,
 };
 
-const VK_P: i32 = 0x50;
+/// Reads the keyboard every frame, remembering which keys are down
+#[derive(Debug, Clone, Default)]
+pub struct Listener {
+    /// keys down as of the last poll
+    held: Vec<Key>,
+    /// if true, the terminal reports key releases
+    enhanced: bool,
+}
 
-#[cfg(target_os = "windows")]
-pub fn get_key() -> Option<String> {
-    if unsafe { GetAsyncKeyState(VK_SPACE) } & 0x8000u16 as i16 != 0 {
-        return Some(" ".to_string());
-    }
-    // Check if the Left arrow key (VK_LEFT) is pressed
-    if unsafe { GetAsyncKeyState(VK_LEFT) } & 0x8000u16 as i16 != 0 {
-        return Some("left".to_string());
-    }
-    // Check if the Right arrow key (VK_RIGHT) is pressed
-    if unsafe { GetAsyncKeyState(VK_RIGHT) } & 0x8000u16 as i16 != 0 {
-        return Some("right".to_string());
+impl Listener {
+    pub fn new() -> Self {
+        Self::default()
     }
-    if unsafe { GetAsyncKeyState(VK_ESCAPE) } & 0x8000u16 as i16 != 0 {
-        return Some("esc".to_string());
+
+    /// Asks the terminal to report key releases if it supports it.
+    ///
+    /// Raw mode must be enabled first, and [`Listener::disable()`] called before exiting.
+    pub fn enable(&mut self) {
+        if terminal::supports_keyboard_enhancement().unwrap_or(false) {
+            // plain keys, like the space bar, only report releases as escape codes
+            let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                | KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES;
+            self.enhanced = execute!(stdout(), PushKeyboardEnhancementFlags(flags)).is_ok();
+        }
     }
-    if unsafe { GetAsyncKeyState(VK_P) } & 0x8000u16 as i16 != 0 {
-        return Some("p".to_string());
+
+    /// Restores the terminal's keyboard reporting
+    pub fn disable(&mut self) {
+        if self.enhanced {
+            let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+            self.enhanced = false;
+        }
+        self.held.clear();
     }
-    None
-}
 
-#[cfg(not(target_os = "windows"))]
-pub fn get_key() -> Option<String> {
-    if event::poll(std::time::Duration::from_millis(10)).unwrap() {
-        if let Ok(Event::Key(KeyEvent { code, kind, .. })) = event::read() {
-            if kind == event::KeyEventKind::Release {
-                return None;
+    /// Returns the actions held and pressed since the last poll
+    #[cfg(target_os = "windows")]
+    pub fn poll(&mut self, bindings: &KeyBindings) -> InputState {
+        let mut input = InputState::new();
+        let mut held: Vec<Key> = Vec::new();
+        for (key, action) in bindings.iter() {
+            if unsafe { GetAsyncKeyState(virtual_key(key)) } & 0x8000u16 as i16 == 0 {
+                continue;
+            }
+            match self.held.contains(&key) {
+                true => input.hold(action),
+                false => input.press(action),
             }
-            return match code {
-                KeyCode::Esc => Some("esc".to_string()),
-                KeyCode::Right => Some("right".to_string()),
-                KeyCode::Left => Some("left".to_string()),
-                KeyCode::Char(c) => match c {
-                    ' ' => Some(c.to_string()),
-                    'p' => Some(c.to_string()),
-                    _ => None,
+            held.push(key);
+        }
+        self.held = held;
+        input
+    }
+
+    /// Returns the actions held and pressed since the last poll
+    #[cfg(not(target_os = "windows"))]
+    pub fn poll(&mut self, bindings: &KeyBindings) -> InputState {
+        let mut pressed: Vec<Key> = Vec::new();
+        if !self.enhanced {
+            // no releases are reported, keys are only down when an event says so
+            self.held.clear();
+        }
+        let mut timeout = Duration::from_millis(10);
+        // draining every pending event, not just the first one
+        while event::poll(timeout).unwrap_or(false) {
+            timeout = Duration::ZERO;
+            let (key, kind) = match event::read() {
+                Ok(Event::Key(KeyEvent { code, kind, .. })) => match key_of(code) {
+                    Some(key) => (key, kind),
+                    None => continue,
                 },
-                _ => None,
+                // resizes included, the renderer checks the terminal size on
+                // every frame so they're picked up there on every platform
+                _ => continue,
             };
+            match kind {
+                KeyEventKind::Press | KeyEventKind::Repeat => {
+                    pressed.push(key);
+                    if !self.held.contains(&key) {
+                        self.held.push(key);
+                    }
+                }
+                KeyEventKind::Release => self.held.retain(|held| *held != key),
+            }
+        }
+        let mut input = InputState::new();
+        for (key, action) in bindings.iter() {
+            if pressed.contains(&key) {
+                input.press(action);
+            } else if self.held.contains(&key) {
+                input.hold(action);
+            }
         }
+        input
+    }
+}
+
+/// Returns the virtual key code of a key
+#[cfg(target_os = "windows")]
+fn virtual_key(key: Key) -> i32 {
+    match key {
+        Key::Left => VK_LEFT,
+        Key::Right => VK_RIGHT,
+        Key::Up => VK_UP,
+        Key::Down => VK_DOWN,
+        Key::Space => VK_SPACE,
+        Key::Enter => VK_RETURN,
+        Key::Tab => VK_TAB,
+        Key::Esc => VK_ESCAPE,
+        // letters and digits share their uppercase ASCII code
+        Key::Char(c) => c.to_ascii_uppercase() as i32,
+    }
+}
+
+/// Returns the key behind a crossterm key code, if the game knows it
+#[cfg(not(target_os = "windows"))]
+fn key_of(code: KeyCode) -> Option<Key> {
+    match code {
+        KeyCode::Esc => Some(Key::Esc),
+        KeyCode::Right => Some(Key::Right),
+        KeyCode::Left => Some(Key::Left),
+        KeyCode::Up => Some(Key::Up),
+        KeyCode::Down => Some(Key::Down),
+        KeyCode::Enter => Some(Key::Enter),
+        KeyCode::Tab => Some(Key::Tab),
+        KeyCode::Char(c) => Key::from_char(c),
+        _ => None,
     }
-    None
 }
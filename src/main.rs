@@ -2,79 +2,253 @@
 //! Main file for running the ASCII Space Invaders game.
 //!
 //! Sets up the game, runs the main loop handling input, updates, and rendering,
-//! and shows the final result when the game ends.
+//! and shows the final result when the game ends, asking for the player's name
+//! when the game made it into the high scores.
+mod cli;
+mod config;
 mod engine;
 mod entities;
 mod errors;
+mod fire_control;
+mod formation;
+mod highscores;
+mod input;
 mod listener;
+mod palette;
+mod powerups;
+mod replay;
+mod rng;
+mod score;
 mod space_invaders;
 mod utils;
+mod wave;
+mod weapon;
 
+use cli::Args;
+use config::Config;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use crossterm::terminal;
 use engine::{
     sprite::{self, Sprite, State},
     Coordinate, Engine,
 };
 use errors::{Error, ErrorKind};
-//use listener::{get_key, key_pressed};
-use space_invaders::SpaceInvaders;
+use formation::Formations;
+use highscores::{HighScore, HighScores, MAX_NAME_LEN};
+use input::Action;
+use listener::Listener;
+use palette::Palette;
+use replay::{rules_hash, Recorder, Replay, ReplayFrame};
+use rng::Rng;
+use space_invaders::{SpaceInvaders, STATUS_LINES};
 
 use std::cell::RefCell;
+use std::io::{stdout, Write};
 use std::process::exit;
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 use std::thread::{self, JoinHandle};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-pub const ALIEN_COL_COUNT: usize = 6;
-const PLANE_DIMENSIONS: Coordinate = (100, 25); // (WIDTH, HEIGHT)
+const PLANE_DIMENSIONS: Coordinate = (100, 25); // (WIDTH, HEIGHT), when the terminal size is unknown
+const MIN_PLANE_DIMENSIONS: Coordinate = (40, 20); // (WIDTH, HEIGHT)
 
 //const PIXEL_CHAR: char = '█';
 //const PIXEL_CHAR: char = '▀';
 pub const PIXEL_CHAR: char = '⨊';
 pub const BACKGROUND_CHAR: char = '.';
+pub const PALETTE: Palette = Palette::ARCADE;
 
-pub const OBSTACLE_WAIT_TIME: f32 = 2.0; // seconds
-pub const OBSTACLE_SPEED: f32 = 65.0;
+pub const SPEEDSTER_BULLET_PER_DELTA: f32 = 2.0;
 
-pub const ALIEN_STEP_PER_DELTA: f32 = 15.0;
-pub const BULLET_STEP_PER_DELTA: f32 = 9.0;
+pub const FIXED_TIMESTEP: f32 = 1.0 / 60.0; // seconds simulated per update
+pub const MAX_FRAME_TIME: f32 = 0.25; // seconds
 
-//pub const SHOOTER_STEP_PER_DELTA: f32 = 90.0;
-pub const SHOOTER_STEP_PER_DELTA: f32 = if cfg!(target_os = "windows") {
-    30.0
-} else {
-    90.0
-};
-pub const SPEEDSTER_STEP_PER_DELTA: f32 = 90.0;
-pub const SPEEDSTER_BULLET_PER_DELTA: f32 = 2.0;
+/// Picks the plane size from the arguments, filling in whatever
+/// wasn't given with what fits in the terminal
+fn plane_dimensions(args: &Args) -> Result<Coordinate, Error> {
+    let fitting: Coordinate = match terminal::size() {
+        // leaving room for the status lines and a spare line
+        // so the last row doesn't make the terminal scroll
+        Ok((columns, rows)) => (
+            columns as usize,
+            (rows as usize).saturating_sub(STATUS_LINES + 1),
+        ),
+        Err(_) => PLANE_DIMENSIONS,
+    };
+    let dimensions = (
+        args.width.unwrap_or(fitting.0),
+        args.height.unwrap_or(fitting.1),
+    );
+    if dimensions.0 < MIN_PLANE_DIMENSIONS.0 || dimensions.1 < MIN_PLANE_DIMENSIONS.1 {
+        return Err(Error::new(
+            ErrorKind::InvalidArgument,
+            format!(
+                "The plane must be at least {}x{}, got {}x{}",
+                MIN_PLANE_DIMENSIONS.0, MIN_PLANE_DIMENSIONS.1, dimensions.0, dimensions.1
+            ),
+        ));
+    }
+    Ok(dimensions)
+}
 
-fn main() -> Result<(), Error> {
-    terminal::enable_raw_mode().expect("Error at enabling raw mode");
-    let mut game = SpaceInvaders::new(PLANE_DIMENSIONS)?;
-    game.set_up();
+/// Reads the player's name on the line below the cursor, returns
+/// `None` if they'd rather not enter one by pressing escape
+fn enter_name(width: usize) -> Option<String> {
+    let prompt = format!(
+        "New high score! Enter your name (up to {} characters): ",
+        MAX_NAME_LEN
+    );
+    let padding = " ".repeat(width.saturating_sub(prompt.chars().count() + MAX_NAME_LEN) / 2);
+    // dropping whatever was typed while playing
+    while event::poll(Duration::ZERO).unwrap_or(false) {
+        let _ = event::read();
+    }
+    let mut name = String::new();
+    loop {
+        // raw mode, every line is moved back to the start by hand
+        print!("\r{}{}{}\x1B[K", padding, prompt, name);
+        stdout().flush().expect("Error at flush to stdout");
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => key,
+            Ok(_) => continue,
+            Err(_) => return None,
+        };
+        match key.code {
+            KeyCode::Enter => break,
+            KeyCode::Esc => return None,
+            KeyCode::Backspace => {
+                let _ = name.pop();
+            }
+            KeyCode::Char(c) if !c.is_control() && name.chars().count() < MAX_NAME_LEN => {
+                name.push(c)
+            }
+            _ => (),
+        }
+    }
+    print!("\r\n");
+    Some(name)
+}
+
+/// Runs the game until it's over, the player quits or the replay ends,
+/// recording every frame if there's a recorder
+fn play(
+    game: &mut SpaceInvaders,
+    listener: &mut Listener,
+    mut replay: Option<&mut Replay>,
+    mut recorder: Option<&mut Recorder>,
+) -> Result<(), Error> {
     let mut delta_time: f32 = 0.0;
-    let game_timer = Instant::now();
     loop {
+        if !game.fits() {
+            // waiting for a bigger terminal outside of the simulation,
+            // so neither the game nor a replay moves on meanwhile
+            game.draw();
+            let input = listener.poll(&config::get().keys);
+            if input.is_pressed(Action::Quit) {
+                game.handle_input(input);
+                break;
+            }
+            // polling doesn't block on every platform, sleeping
+            // a tick so the wait doesn't keep a core busy
+            utils::sleep(FIXED_TIMESTEP as f64);
+            continue;
+        }
         let start = Instant::now();
-        game.handle_input();
-        game.update(delta_time);
+        let frame = match replay.as_mut() {
+            Some(replay) => match replay.next_frame() {
+                Some(frame) => {
+                    // playing the frame back at the pace it was recorded
+                    utils::sleep(frame.delta_time as f64);
+                    frame
+                }
+                None => break,
+            },
+            None => ReplayFrame {
+                delta_time,
+                input: listener.poll(&config::get().keys),
+            },
+        };
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(&frame)?;
+        }
+        game.handle_input(frame.input);
+        game.tick(frame.delta_time);
         game.draw();
         if game.game_over() {
             break;
         }
         delta_time = (Instant::now() - start).as_secs_f32();
     }
-    let msg = match game.won() {
-        true => "You won :)",
-        false => "You lost :(",
+    Ok(())
+}
+
+fn main() -> Result<(), Error> {
+    let args = Args::parse()?;
+    config::set(Config::load(args.config.as_deref())?);
+    formation::set(Formations::load(args.formations.as_deref())?);
+    // a replay is only played back under the rules it was recorded with
+    let rules = rules_hash(config::get(), formation::get());
+    let mut replay: Option<Replay> = match &args.replay {
+        Some(path) => Some(Replay::load(path, rules)?),
+        None => None,
+    };
+    // a replay only plays back the same game on the plane it was recorded on
+    let dimensions = match replay.as_ref().map(|r| r.dimensions()) {
+        Some(dimensions) => dimensions,
+        None => plane_dimensions(&args)?,
+    };
+    // and with the same random numbers
+    let seed = match replay.as_ref().map(|r| r.seed()) {
+        Some(seed) => seed,
+        None => args.seed.unwrap_or_else(Rng::random_seed),
     };
-    println!("{:^100}\n{:^100}", ' ', msg);
+    let mut recorder: Option<Recorder> = match &args.record {
+        Some(path) => Some(Recorder::create(path, dimensions, seed, rules)?),
+        None => None,
+    };
+    let mut high_scores = HighScores::load_default();
+    let mut game = SpaceInvaders::new(dimensions)?
+        .with_seed(seed)
+        .with_high_scores(high_scores.clone());
+    game.set_up();
+    terminal::enable_raw_mode().expect("Error at enabling raw mode");
+    let mut listener = Listener::new();
+    listener.enable();
+    let game_timer = Instant::now();
+    if let Err(error) = play(&mut game, &mut listener, replay.as_mut(), recorder.as_mut()) {
+        // leaving the terminal as it was found before bailing out
+        listener.disable();
+        terminal::disable_raw_mode().expect("Error at disabling raw mode");
+        return Err(error);
+    }
+    let msg = match game.game_over() {
+        true => format!("Game over! You made it to wave {}", game.wave()),
+        false => "Replay finished".to_string(),
+    };
+    let width = dimensions.0;
+    println!("{:^width$}\n{:^width$}", ' ', msg);
+    let score_result = format!("Your score: {}", game.score());
+    println!("{:^width$}", score_result);
     let time_result = format!(
         "You finished the game in: {:?}",
         Instant::now() - game_timer
     );
-    println!("{:^100}\n{:^100}\n", ' ', time_result);
+    println!("{:^width$}\n{:^width$}\n", ' ', time_result);
+    listener.disable();
+    // a replay is a game that was already played
+    if replay.is_none() && high_scores.qualifies(game.score()) {
+        if let Some(name) = enter_name(width) {
+            let entry = HighScore::new(&name, game.score(), game.wave(), game.play_time());
+            if let Some(rank) = high_scores.insert(entry) {
+                let message = match high_scores.save() {
+                    Ok(_) => format!("You're #{} on the high scores!", rank),
+                    Err(error) => error.diagnosis(),
+                };
+                print!("{:^width$}\r\n", message);
+            }
+        }
+    }
     terminal::disable_raw_mode().expect("Error at disabling raw mode");
     Ok(())
 }
@@ -0,0 +1,77 @@
+//! Pixel Palettes
+//!
+//! The engine draws every "pixel" with the glyph of the sprite it belongs to. A
+//! [`Palette`] groups the glyph, character and colors, used by each kind of entity,
+//! so the shooter, aliens, bullets, obstacles, bunkers, the UFO, power-ups and the speedster
+//! can be told apart on screen.
+//!
+//! # Palettes
+//! - [`Palette::ARCADE`]: A different glyph and color for every entity, the default.
+//! - [`Palette::CLASSIC`]: Every entity drawn with the same `⨊` and no colors, the original look.
+//!
+//! Colors are dropped by the renderer when `NO_COLOR` is set or the terminal
+//! doesn't support them, only the characters remain.
+use crate::engine::Glyph;
+
+use crossterm::style::Color;
+
+/// The glyph each kind of entity is drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub shooter: Glyph,
+    /// the shooter flashes between this glyph and its own while it can't be hit
+    pub shooter_invulnerable: Glyph,
+    pub alien: Glyph,
+    pub bullet: Glyph,
+    pub alien_bullet: Glyph,
+    pub speedster: Glyph,
+    /// the speedster flashes between this glyph and its own after being hit
+    pub speedster_hit: Glyph,
+    pub obstacle: Glyph,
+    pub bunker: Glyph,
+    pub ufo: Glyph,
+    /// colors of the bonus shown where the UFO was shot down, the digits replace the character
+    pub ufo_bonus: Glyph,
+    /// the player's bullets while they pierce through aliens
+    pub piercing_bullet: Glyph,
+    /// colors of the falling power-up capsules, the power-up's symbol replaces the character
+    pub capsule: Glyph,
+    /// colors of the debris flying off whatever was hit, each frame replaces the character
+    pub explosion: Glyph,
+}
+
+impl Palette {
+    pub const ARCADE: Palette = Palette {
+        shooter: Glyph::new('█').fg(Color::Green),
+        shooter_invulnerable: Glyph::new('░').fg(Color::Green),
+        alien: Glyph::new('⨊').fg(Color::Cyan),
+        bullet: Glyph::new('|').fg(Color::Yellow),
+        alien_bullet: Glyph::new('¦').fg(Color::Red),
+        speedster: Glyph::new('≡').fg(Color::Magenta),
+        speedster_hit: Glyph::new('≡').fg(Color::White).bg(Color::Red),
+        obstacle: Glyph::new('▒').fg(Color::White),
+        bunker: Glyph::new('▓').fg(Color::Green),
+        ufo: Glyph::new('▀').fg(Color::Red),
+        ufo_bonus: Glyph::new('0').fg(Color::Yellow),
+        piercing_bullet: Glyph::new('‖').fg(Color::Magenta),
+        capsule: Glyph::new('S').fg(Color::Black).bg(Color::Yellow),
+        explosion: Glyph::new('*').fg(Color::DarkYellow),
+    };
+
+    pub const CLASSIC: Palette = Palette {
+        shooter: Glyph::new('⨊'),
+        shooter_invulnerable: Glyph::new('⨊'),
+        alien: Glyph::new('⨊'),
+        bullet: Glyph::new('⨊'),
+        alien_bullet: Glyph::new('⨊'),
+        speedster: Glyph::new('⨊'),
+        speedster_hit: Glyph::new('⨊'),
+        obstacle: Glyph::new('⨊'),
+        bunker: Glyph::new('⨊'),
+        ufo: Glyph::new('⨊'),
+        ufo_bonus: Glyph::new('0'),
+        piercing_bullet: Glyph::new('⨊'),
+        capsule: Glyph::new('S'),
+        explosion: Glyph::new('*'),
+    };
+}
@@ -0,0 +1,123 @@
+//! Power-ups
+//!
+//! Aliens shot down by the player sometimes drop a capsule that falls towards the
+//! bottom of the plane. Catching it with the shooter grants the power-up written
+//! on it, most of them lasting `powerups.duration` seconds.
+//!
+//! # Power-ups
+//! - Spread (`S`): every shot fires three bullets side by side.
+//! - Rapid (`R`): the shooter fires twice as often, its bullets flying twice as fast.
+//! - Piercing (`P`): the player's bullets go through the aliens they hit.
+//! - Shield (`O`): hits are shrugged off.
+//! - Extra life (`+`): one more life, right away.
+//!
+//! Catching a power-up that's already active starts its time over. Losing a life
+//! takes every active power-up with it.
+//!
+//! # Example
+//! ```rust
+//! use crate::powerups::{PowerUp, PowerUps};
+//!
+//! let mut power_ups = PowerUps::new();
+//! power_ups.activate(PowerUp::Spread, &clock);
+//! assert!(power_ups.is_active(PowerUp::Spread, &clock));
+//! ```
+use crate::config;
+use crate::engine::clock::{Clock, Timer};
+
+/// What a capsule grants once caught
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerUp {
+    Spread,
+    Rapid,
+    Piercing,
+    Shield,
+    ExtraLife,
+}
+
+impl PowerUp {
+    /// Every power-up, in the order they're shown in
+    pub const ALL: [PowerUp; 5] = [
+        PowerUp::Spread,
+        PowerUp::Rapid,
+        PowerUp::Piercing,
+        PowerUp::Shield,
+        PowerUp::ExtraLife,
+    ];
+
+    /// Name shown in the HUD
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUp::Spread => "SPREAD",
+            PowerUp::Rapid => "RAPID",
+            PowerUp::Piercing => "PIERCE",
+            PowerUp::Shield => "SHIELD",
+            PowerUp::ExtraLife => "1UP",
+        }
+    }
+
+    /// Character the capsule is drawn with
+    pub fn symbol(&self) -> char {
+        match self {
+            PowerUp::Spread => 'S',
+            PowerUp::Rapid => 'R',
+            PowerUp::Piercing => 'P',
+            PowerUp::Shield => 'O',
+            PowerUp::ExtraLife => '+',
+        }
+    }
+
+    /// Returns false for power-ups that take effect once, right away
+    pub fn is_timed(&self) -> bool {
+        *self != PowerUp::ExtraLife
+    }
+}
+
+/// The timed power-ups the player has
+#[derive(Debug, Clone, Default)]
+pub struct PowerUps {
+    /// every active power-up, started when it was caught
+    active: Vec<(PowerUp, Timer)>,
+}
+
+impl PowerUps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a timed power-up, or starts its time over if it's already active
+    pub fn activate(&mut self, power_up: PowerUp, clock: &Clock) {
+        if !power_up.is_timed() {
+            return;
+        }
+        self.active.retain(|(active, _)| *active != power_up);
+        self.active.push((power_up, Timer::start(clock)));
+    }
+
+    pub fn is_active(&self, power_up: PowerUp, clock: &Clock) -> bool {
+        self.time_left(power_up, clock).is_some()
+    }
+
+    /// Returns the seconds the power-up lasts for, `None` if it isn't active
+    pub fn time_left(&self, power_up: PowerUp, clock: &Clock) -> Option<f32> {
+        let duration = config::get().powerup_duration;
+        self.active
+            .iter()
+            .find(|(active, _)| *active == power_up)
+            .map(|(_, timer)| duration - timer.elapsed(clock))
+            .filter(|left| *left > 0.0)
+    }
+
+    /// Returns the active power-ups and the seconds each has left, in the order they're shown in
+    pub fn active(&self, clock: &Clock) -> Vec<(PowerUp, f32)> {
+        PowerUp::ALL
+            .iter()
+            .filter_map(|power_up| Some((*power_up, self.time_left(*power_up, clock)?)))
+            .collect()
+    }
+
+    /// Drops every active power-up, e.g. when the player loses a life
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+}
@@ -0,0 +1,406 @@
+//! Input Recording and Replay
+//!
+//! Because the simulation runs on a fixed timestep, a game is fully described by
+//! the input seen on every frame together with how long that frame took. This module
+//! writes that stream to a file while playing and reads it back, so a game can be
+//! reproduced exactly, e.g. to attach to a bug report.
+//!
+//! # File Format
+//! A plain text file with a header line, the plane dimensions the game was played
+//! on, the seed of its random numbers and a hash of the rules it was played by,
+//! followed by one line per frame:
+//!
+//! ```text
+//! # spaceinvaders replay v1
+//! # plane 100x25
+//! # seed 1760745600123
+//! # rules 5f0c3a9e21d47b68
+//! 0.016734	-	-
+//! 0.017001	move_left	move_left
+//! 0.016250	move_left	fire
+//! ```
+//!
+//! Every frame line holds the frame's delta time, the actions held and the actions
+//! pressed, separated by tabs. Several actions are separated by commas and a `-`
+//! stands for none. Actions are recorded rather than keys so a replay plays back
+//! the same no matter how keys are bound.
+//!
+//! The rules are the configuration, key bindings aside, and the formations. A
+//! replay is only played back under the same rules, see [`rules_hash()`].
+//!
+//! # Example
+//! ```rust
+//! use crate::input::{Action, InputState};
+//! use crate::replay::{rules_hash, Recorder, Replay, ReplayFrame};
+//!
+//! let mut input = InputState::new();
+//! input.press(Action::MoveLeft);
+//! let rules = rules_hash(config::get(), formation::get());
+//! let mut recorder = Recorder::create("game.replay", (100, 25), 42, rules)?;
+//! recorder.record(&ReplayFrame { delta_time: 0.016, input })?;
+//!
+//! let mut replay = Replay::load("game.replay", rules)?;
+//! while let Some(frame) = replay.next_frame() {
+//!     game.handle_input(frame.input);
+//!     game.tick(frame.delta_time);
+//! }
+//! ```
+use crate::config::Config;
+use crate::engine::Coordinate;
+use crate::errors::{Error, ErrorKind};
+use crate::formation::Formations;
+use crate::input::{Action, InputState, KeyBindings};
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+const HEADER: &str = "# spaceinvaders replay v1";
+const PLANE_PREFIX: &str = "# plane ";
+const SEED_PREFIX: &str = "# seed ";
+const RULES_PREFIX: &str = "# rules ";
+
+/// Hashes everything besides the input and the seed that a game plays out
+/// from: the configuration, but for the key bindings, and the formations
+pub fn rules_hash(config: &Config, formations: &Formations) -> u64 {
+    let config = Config {
+        keys: KeyBindings::default(),
+        ..config.clone()
+    };
+    // FNV-1a, which unlike the standard library's hasher is the same on every build
+    format!("{:?}{:?}", config, formations)
+        .bytes()
+        .fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+        })
+}
+
+/// The input seen on a single frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayFrame {
+    pub delta_time: f32,
+    pub input: InputState,
+}
+
+impl ReplayFrame {
+    /// Converts the frame into a line of the replay file
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}",
+            self.delta_time,
+            join_actions(&self.input.held()),
+            join_actions(&self.input.pressed())
+        )
+    }
+
+    /// Parses a line of a replay file
+    fn from_line(line: &str, number: usize) -> Result<Self, Error> {
+        let invalid = |reason: &str| {
+            Error::new(
+                ErrorKind::InvalidReplay,
+                format!("line {}: {}, found `{}`", number, reason, line),
+            )
+        };
+        let (delta_time, held, pressed) = match line.split('\t').collect::<Vec<&str>>()[..] {
+            [delta_time, held, pressed] => (delta_time, held, pressed),
+            _ => return Err(invalid("expected `<delta time>\\t<held>\\t<pressed>`")),
+        };
+        let delta_time: f32 = delta_time
+            .parse()
+            .map_err(|_| invalid("delta time is not a number"))?;
+        let unknown = |name: String| invalid(&format!("unknown action `{}`", name));
+        let mut input = InputState::new();
+        for action in split_actions(held).map_err(unknown)? {
+            input.hold(action);
+        }
+        for action in split_actions(pressed).map_err(unknown)? {
+            input.press(action);
+        }
+        Ok(Self { delta_time, input })
+    }
+}
+
+/// Writes frames to a replay file as they are played
+#[derive(Debug)]
+pub struct Recorder {
+    file: BufWriter<File>,
+}
+
+impl Recorder {
+    /// Creates, or truncates, the replay file at the given path, recording the
+    /// plane dimensions the game is played on, its seed and the hash of its rules
+    pub fn create(
+        path: &str,
+        dimensions: Coordinate,
+        seed: u64,
+        rules: u64,
+    ) -> Result<Self, Error> {
+        let file = File::create(path).map_err(|e| {
+            Error::new(
+                ErrorKind::Io,
+                format!("Could not create replay `{}`: {}", path, e),
+            )
+        })?;
+        let mut recorder = Self {
+            file: BufWriter::new(file),
+        };
+        recorder.write_line(HEADER)?;
+        recorder.write_line(&format!(
+            "{}{}x{}",
+            PLANE_PREFIX, dimensions.0, dimensions.1
+        ))?;
+        recorder.write_line(&format!("{}{}", SEED_PREFIX, seed))?;
+        recorder.write_line(&format!("{}{:016x}", RULES_PREFIX, rules))?;
+        Ok(recorder)
+    }
+
+    /// Appends a frame to the replay file.
+    ///
+    /// The file is flushed on every frame so a replay survives the game panicking.
+    pub fn record(&mut self, frame: &ReplayFrame) -> Result<(), Error> {
+        self.write_line(&frame.to_line())
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), Error> {
+        writeln!(self.file, "{}", line)
+            .and_then(|_| self.file.flush())
+            .map_err(|e| Error::new(ErrorKind::Io, format!("Could not write replay: {}", e)))
+    }
+}
+
+/// Frames read from a replay file, handed back in order
+#[derive(Debug, Clone)]
+pub struct Replay {
+    frames: VecDeque<ReplayFrame>,
+    /// plane the replay was recorded on
+    dimensions: Coordinate,
+    /// seed of the recorded game's random numbers
+    seed: u64,
+}
+
+impl Replay {
+    /// Reads and validates the whole replay file at the given path, which
+    /// has to have been recorded under the rules with the given hash
+    pub fn load(path: &str, rules: u64) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            Error::new(
+                ErrorKind::Io,
+                format!("Could not read replay `{}`: {}", path, e),
+            )
+        })?;
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(Error::new(
+                ErrorKind::InvalidReplay,
+                format!("`{}` does not start with `{}`", path, HEADER),
+            ));
+        }
+        let mut frames = VecDeque::new();
+        let mut dimensions: Option<Coordinate> = None;
+        let mut seed: Option<u64> = None;
+        let mut recorded_rules: Option<u64> = None;
+        for (i, line) in lines.enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(plane) = line.strip_prefix(PLANE_PREFIX) {
+                dimensions = Some(parse_dimensions(plane).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidReplay,
+                        format!("line {}: invalid plane `{}`", i + 2, plane),
+                    )
+                })?);
+                continue;
+            }
+            if let Some(value) = line.strip_prefix(SEED_PREFIX) {
+                seed = Some(value.parse().map_err(|_| {
+                    Error::new(
+                        ErrorKind::InvalidReplay,
+                        format!("line {}: invalid seed `{}`", i + 2, value),
+                    )
+                })?);
+                continue;
+            }
+            if let Some(value) = line.strip_prefix(RULES_PREFIX) {
+                recorded_rules = Some(u64::from_str_radix(value, 16).map_err(|_| {
+                    Error::new(
+                        ErrorKind::InvalidReplay,
+                        format!("line {}: invalid rules `{}`", i + 2, value),
+                    )
+                })?);
+                continue;
+            }
+            // the header is line 1
+            frames.push_back(ReplayFrame::from_line(line, i + 2)?);
+        }
+        let missing = |prefix: &str| {
+            Error::new(
+                ErrorKind::InvalidReplay,
+                format!("`{}` has no `{}` line", path, prefix.trim()),
+            )
+        };
+        if recorded_rules.ok_or_else(|| missing(RULES_PREFIX))? != rules {
+            return Err(Error::new(
+                ErrorKind::InvalidReplay,
+                format!(
+                    "`{}` was recorded with a different configuration or formations",
+                    path
+                ),
+            ));
+        }
+        Ok(Self {
+            frames,
+            dimensions: dimensions.ok_or_else(|| missing(PLANE_PREFIX))?,
+            seed: seed.ok_or_else(|| missing(SEED_PREFIX))?,
+        })
+    }
+
+    /// Returns the plane dimensions the replay was recorded on
+    pub fn dimensions(&self) -> Coordinate {
+        self.dimensions
+    }
+
+    /// Returns the seed of the recorded game's random numbers
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the next frame, or `None` once the replay is over
+    pub fn next_frame(&mut self) -> Option<ReplayFrame> {
+        self.frames.pop_front()
+    }
+}
+
+/// Parses dimensions written as `<width>x<height>`
+fn parse_dimensions(s: &str) -> Option<Coordinate> {
+    let (width, height) = s.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+/// Writes actions as a comma separated list, `-` if there are none
+fn join_actions(actions: &[Action]) -> String {
+    if actions.is_empty() {
+        return "-".to_string();
+    }
+    let names: Vec<&str> = actions.iter().map(|action| action.name()).collect();
+    names.join(",")
+}
+
+/// Parses a list written by `join_actions`, returning the first unknown name on failure
+fn split_actions(s: &str) -> Result<Vec<Action>, String> {
+    if s == "-" {
+        return Ok(Vec::new());
+    }
+    s.split(',')
+        .map(|name| Action::from_name(name).ok_or_else(|| name.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Key;
+    use crate::{config, formation};
+
+    /// Returns a path in the temporary directory, unique to the test
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("spaceinvaders-{}.replay", name));
+        path.to_string_lossy().into_owned()
+    }
+
+    fn rules() -> u64 {
+        rules_hash(config::get(), formation::get())
+    }
+
+    fn rejected(name: &str, contents: &str) -> String {
+        let path = temp_path(name);
+        std::fs::write(&path, contents).unwrap();
+        let error = Replay::load(&path, rules()).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(error.kind(), ErrorKind::InvalidReplay);
+        error.diagnosis().replace(&path, "<path>")
+    }
+
+    #[test]
+    fn recorded_frames_play_back() {
+        let path = temp_path("round-trip");
+        let mut input = InputState::new();
+        input.hold(Action::MoveLeft);
+        input.press(Action::Fire);
+        let frames = vec![
+            ReplayFrame {
+                delta_time: 0.016,
+                input: InputState::new(),
+            },
+            ReplayFrame {
+                delta_time: 0.25,
+                input,
+            },
+        ];
+        let mut recorder = Recorder::create(&path, (80, 20), 42, rules()).unwrap();
+        for frame in &frames {
+            recorder.record(frame).unwrap();
+        }
+        drop(recorder);
+
+        let mut replay = Replay::load(&path, rules()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(replay.dimensions(), (80, 20));
+        assert_eq!(replay.seed(), 42);
+        assert_eq!(replay.next_frame(), Some(frames[0].clone()));
+        assert_eq!(replay.next_frame(), Some(frames[1].clone()));
+        assert_eq!(replay.next_frame(), None);
+    }
+
+    #[test]
+    fn rules_ignore_key_bindings() {
+        let mut config = config::get().clone();
+        config.keys = KeyBindings::default();
+        config
+            .keys
+            .bind(Action::Fire, vec![Key::Char('w')])
+            .unwrap();
+        assert_eq!(rules_hash(&config, formation::get()), rules());
+        config.shooter_lives += 1;
+        assert_ne!(rules_hash(&config, formation::get()), rules());
+    }
+
+    #[test]
+    fn replays_under_other_rules_are_rejected() {
+        let contents = format!(
+            "{}\n# plane 80x20\n# seed 42\n# rules {:016x}\n",
+            HEADER,
+            rules().wrapping_add(1)
+        );
+        assert_eq!(
+            rejected("other-rules", &contents),
+            "`<path>` was recorded with a different configuration or formations"
+        );
+    }
+
+    #[test]
+    fn malformed_replays_are_rejected() {
+        assert_eq!(
+            rejected("no-header", "0.016\t-\t-\n"),
+            format!("`<path>` does not start with `{}`", HEADER)
+        );
+        let header = format!("{}\n# plane 80x20\n# seed 42\n", HEADER);
+        assert_eq!(
+            rejected("no-rules", &header),
+            "`<path>` has no `# rules` line"
+        );
+        let header = format!("{}# rules {:016x}\n", header, rules());
+        assert_eq!(
+            rejected("short-frame", &format!("{}0.016\tfire\n", header)),
+            "line 5: expected `<delta time>\\t<held>\\t<pressed>`, found `0.016\tfire`"
+        );
+        assert_eq!(
+            rejected("unknown-action", &format!("{}0.016\t-\tjump\n", header)),
+            "line 5: unknown action `jump`, found `0.016\t-\tjump`"
+        );
+        assert_eq!(
+            rejected("bad-seed", &format!("{}\n# seed lots\n", HEADER)),
+            "line 2: invalid seed `lots`"
+        );
+    }
+}
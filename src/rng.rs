@@ -0,0 +1,130 @@
+//! Random Numbers
+//!
+//! A small seeded pseudo random number generator (SplitMix64). The game only
+//! ever draws its random numbers from an [`Rng`] it was handed a seed for, so the
+//! same seed and the same input always play out the same game. The seed is
+//! written to replays for that reason.
+//!
+//! It is fast and good enough for gameplay, it is not meant for anything secure.
+//!
+//! # Example
+//! ```rust
+//! use crate::rng::Rng;
+//!
+//! let mut rng = Rng::new(42);
+//! let column = rng.below(10); // 0..10
+//! if rng.chance(0.25) {
+//!     // one time out of four
+//! }
+//! assert_eq!(Rng::new(42).below(10), column);
+//! ```
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Initializer function, the same seed always yields the same numbers
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns a seed taken from the system time, for games that don't ask for one
+    pub fn random_seed() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0.0..1.0`
+    pub fn next_f32(&mut self) -> f32 {
+        // the top 24 bits, as many as an f32 holds exactly
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a number in `0..n`, 0 if `n` is 0
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns true with the given probability, from 0.0 to 1.0
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    /// Returns a random element of the slice, `None` if it is empty
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.below(items.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_splitmix64_reference() {
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+        assert_eq!(rng.next_u64(), 0x06C4_5D18_8009_454F);
+    }
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(Rng::new(42).next_u64(), Rng::new(43).next_u64());
+    }
+
+    #[test]
+    fn numbers_stay_in_range() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            assert!((0.0..1.0).contains(&rng.next_f32()));
+            assert!(rng.below(3) < 3);
+        }
+        assert_eq!(rng.below(0), 0);
+        assert_eq!(rng.below(1), 0);
+    }
+
+    #[test]
+    fn chances_at_the_edges_are_certain() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            assert!(!rng.chance(0.0));
+            assert!(rng.chance(1.0));
+        }
+    }
+
+    #[test]
+    fn picks_from_the_slice() {
+        let mut rng = Rng::new(7);
+        let empty: [u8; 0] = [];
+        assert_eq!(rng.pick(&empty), None);
+        assert_eq!(rng.pick(&[5]), Some(&5));
+        let items = [1, 2, 3];
+        let mut picked: Vec<i32> = (0..100).map(|_| *rng.pick(&items).unwrap()).collect();
+        picked.sort();
+        picked.dedup();
+        assert_eq!(picked, items);
+    }
+}
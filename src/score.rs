@@ -0,0 +1,168 @@
+//! Scoring
+//!
+//! Keeps the player's score. Every [`Target`] shot down is worth the points set in
+//! the configuration, multiplied by the current combo.
+//!
+//! # Points
+//! - Aliens: worth more the higher their row in the formation, the top row first.
+//! - Speedster: a bonus for every stage it's beaten through.
+//! - Alien bullets and snowflakes: a few points for every one shot down.
+//! - UFO: a bonus picked at random from `score.ufo` every time one is shot down.
+//!
+//! # Combo
+//! Shooting something down within `combo_window` seconds of the previous hit raises
+//! the multiplier by one, up to `max_multiplier`. Letting the window run out, or
+//! getting hit, brings it back to one.
+//!
+//! # Example
+//! ```rust
+//! use crate::score::{Score, Target};
+//!
+//! let mut score = Score::new();
+//! score.hit(Target::Alien { row: 0 }, &clock);
+//! score.hit(Target::AlienBullet, &clock); // worth double, right after the alien
+//! assert_eq!(score.multiplier(), 2);
+//! ```
+use crate::config;
+use crate::engine::clock::{Clock, Timer};
+
+/// Anything worth points when shot down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// an alien of the given formation row, 0 being the top one
+    Alien {
+        row: usize,
+    },
+    /// the speedster, beaten through the given stage, starting at 1
+    SpeedsterStage(u8),
+    AlienBullet,
+    Snowflake,
+    /// a mystery UFO, worth the bonus it was given
+    Ufo {
+        points: u64,
+    },
+}
+
+impl Target {
+    /// Points the target is worth before the combo multiplier
+    pub fn points(&self) -> u64 {
+        let config = config::get();
+        // rows and stages past the configured ones are worth as much as the last one
+        let nth = |values: &[u64], n: usize| values.get(n).or(values.last()).copied().unwrap_or(0);
+        match self {
+            Target::Alien { row } => nth(&config.score_alien_rows, *row),
+            Target::SpeedsterStage(stage) => nth(
+                &config.score_speedster_stages,
+                (*stage as usize).saturating_sub(1),
+            ),
+            Target::AlienBullet => config.score_alien_bullet,
+            Target::Snowflake => config.score_snowflake,
+            Target::Ufo { points } => *points,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Score {
+    points: u64,
+    /// consecutive hits, each within the combo window of the previous one
+    combo: u64,
+    /// started on the last hit, `None` when there is no combo going
+    combo_timer: Option<Timer>,
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+
+    /// Returns what the last hit was multiplied by, one without a combo going
+    pub fn multiplier(&self) -> u64 {
+        self.combo.clamp(1, config::get().combo_max_multiplier)
+    }
+
+    /// Adds the target's points, multiplied by the combo, and returns them
+    pub fn hit(&mut self, target: Target, clock: &Clock) -> u64 {
+        self.update(clock);
+        self.combo += 1;
+        self.combo_timer = Some(Timer::start(clock));
+        let points = target.points() * self.multiplier();
+        self.points += points;
+        points
+    }
+
+    /// Drops the combo once its window runs out, must be called every update
+    pub fn update(&mut self, clock: &Clock) {
+        if let Some(timer) = self.combo_timer {
+            if timer.elapsed(clock) > config::get().combo_window {
+                self.break_combo();
+            }
+        }
+    }
+
+    /// Brings the multiplier back to one, e.g. when the player is hit
+    pub fn break_combo(&mut self) {
+        self.combo = 0;
+        self.combo_timer = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hits_within_the_window_build_a_combo() {
+        let mut clock = Clock::new();
+        let mut score = Score::new();
+        assert_eq!(score.hit(Target::AlienBullet, &clock), 5);
+        clock.advance(1.0);
+        assert_eq!(score.hit(Target::AlienBullet, &clock), 10);
+        clock.advance(1.0);
+        assert_eq!(score.hit(Target::AlienBullet, &clock), 15);
+        assert_eq!(score.multiplier(), 3);
+        assert_eq!(score.points(), 30);
+    }
+
+    #[test]
+    fn the_combo_drops_once_the_window_runs_out() {
+        let mut clock = Clock::new();
+        let mut score = Score::new();
+        score.hit(Target::AlienBullet, &clock);
+        score.hit(Target::AlienBullet, &clock);
+        clock.advance(1.5);
+        score.update(&clock);
+        assert_eq!(score.multiplier(), 2);
+        clock.advance(0.1);
+        score.update(&clock);
+        assert_eq!(score.multiplier(), 1);
+        assert_eq!(score.hit(Target::AlienBullet, &clock), 5);
+    }
+
+    #[test]
+    fn the_multiplier_stops_at_the_cap() {
+        let clock = Clock::new();
+        let mut score = Score::new();
+        for _ in 0..5 {
+            score.hit(Target::Snowflake, &clock);
+        }
+        assert_eq!(score.multiplier(), 5);
+        assert_eq!(score.hit(Target::Snowflake, &clock), 50);
+        assert_eq!(score.multiplier(), 5);
+    }
+
+    #[test]
+    fn getting_hit_breaks_the_combo() {
+        let clock = Clock::new();
+        let mut score = Score::new();
+        score.hit(Target::Alien { row: 0 }, &clock);
+        score.hit(Target::Alien { row: 0 }, &clock);
+        score.break_combo();
+        assert_eq!(score.multiplier(), 1);
+        assert_eq!(score.hit(Target::Alien { row: 0 }, &clock), 30);
+    }
+}
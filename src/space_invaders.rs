@@ -1,61 +1,134 @@
 //! SpaceInvaders Game Controller
 //!
-//! This module serves as the main controller for the Space Invaders game. It orchestrates 
+//! This module serves as the main controller for the Space Invaders game. It orchestrates
 //! the core gameplay loop by managing the following elements:
 //!
 //! - **Player (Shooter):** Moves horizontally and fires bullets.
-//! - **Enemies (Aliens):** Move in formation and shoot back at the player.
-//! - **Boss (Speedster):** Spawns after all aliens are destroyed, with multiple stages.
+//! - **Enemies (Aliens):** Move in formation, faster as it thins out, and shoot back at the player. Some are
+//!   armored, some break off to dive at the player and some split in two when shot.
+//! - **Mystery UFO:** Crosses the top of the plane now and then, worth a random bonus.
+//! - **Bunkers:** Shelter the player, worn away by bullets and aliens. Rebuilt every wave.
+//! - **Power-ups:** Now and then dropped by the aliens the player shoots down, caught by moving under them.
+//! - **Waves:** Re-spawns a bigger, faster formation every time one is cleared.
+//! - **Boss (Speedster):** Spawns every few waves once the aliens are destroyed, with multiple stages.
 //! - **Bullets:** Track movement and resolve collisions between entities.
 //! - **Input Handling:** Processes user keystrokes (movement, shoot, pause, quit).
-//! - **Game State:** Tracks pause, win, loss, and quit conditions.
-//! - **Rendering:** Refreshes the screen with the current game state.
+//! - **Game State:** Tracks pause, loss, and quit conditions. The game goes on until
+//!   the player runs out of lives or the aliens reach them.
+//! - **High Scores:** Shows the best games played on the title screen when asked to.
+//! - **Rendering:** Hands the current game state to a `Renderer` (the terminal by default).
 //!
 //! It interfaces with the rendering engine and entity systems (like Alien, Shooter, Bullet),
 //! and updates the game world each frame based on user input and elapsed time.
-use crate::engine::sprite::State;
+use crate::config;
+use crate::engine::clock::Timer;
+use crate::engine::renderer::{Renderer, TerminalRenderer};
+use crate::engine::sprite::{Collision, State};
 use crate::engine::Coordinate;
 use crate::engine::Engine;
+use crate::engine::EntityId;
+use crate::engine::LayerId;
 use crate::entities::{
-    Bullet, Shooter, Speedster,
+    spawn_bunkers, BonusLabel, Bullet, Bunker, Capsule, Explosion, Shooter, Speedster, Ufo,
     {
-        farthest_left_alien, farthest_right_alien, find_alien_and_destroy, spawn_aliens, Alien,
-        Direction,
+        farthest_left_alien, farthest_right_alien, find_alien_and_destroy, find_alien_and_hit,
+        front_line, spawn_aliens, split_alien, Alien, AlienHit, Direction,
     },
 };
 use crate::errors::{Error, ErrorKind};
-use crate::listener::get_key;
+use crate::fire_control::FireControl;
+use crate::formation::Behaviour;
+use crate::highscores::HighScores;
+use crate::input::{Action, InputState};
+use crate::powerups::{PowerUp, PowerUps};
+use crate::rng::Rng;
+use crate::score::{Score, Target};
 use crate::utils;
-use crate::{
-    ALIEN_COL_COUNT, ALIEN_STEP_PER_DELTA, BACKGROUND_CHAR, BULLET_STEP_PER_DELTA, PIXEL_CHAR,
-    SHOOTER_STEP_PER_DELTA, SPEEDSTER_STEP_PER_DELTA,
-};
+use crate::wave::Wave;
+use crate::weapon::Weapon;
+use crate::{BACKGROUND_CHAR, FIXED_TIMESTEP, MAX_FRAME_TIME, PIXEL_CHAR};
 
 use std::cell::RefCell;
 use std::rc::Rc;
 
+/// Number of lines drawn above the plane. Always the same
+/// so the plane never moves around the screen
+pub const STATUS_LINES: usize = 3;
+
+/// Seconds the number of a new wave is shown for
+const WAVE_BANNER_DURATION: f32 = 2.0;
+
 #[derive(Clone, Debug)]
 pub struct SpaceInvaders {
     pub(crate) aliens: Vec<Alien>,
+    /// aliens the formation started the wave with
+    pub(crate) formation_size: usize,
+    /// aliens that broke off from the formation to dive at the player
+    pub(crate) divers: Vec<Alien>,
+    /// shelters the player can hide under, worn away by whatever runs into them
+    pub(crate) bunkers: Vec<Bunker>,
+    /// the mystery UFO, while one is crossing the plane
+    pub(crate) ufo: Option<Ufo>,
+    /// started when the last UFO left the plane
+    pub(crate) ufo_timer: Timer,
+    /// the bonus of the last UFO shot down, while it's shown
+    pub(crate) bonus_label: Option<BonusLabel>,
+    /// power-up capsules falling towards the player
+    pub(crate) capsules: Vec<Capsule>,
+    /// the power-ups the player caught, while they last
+    pub(crate) power_ups: PowerUps,
+    /// debris of the last hits, drawn but never collided with
+    pub(crate) explosions: Vec<Explosion>,
     pub(crate) alien_xd: f32,
+    /// frame time that has not yet been simulated
+    pub(crate) accumulator: f32,
     pub(crate) alien_direction: Direction,
     pub(crate) game_over: bool,
     pub(crate) shooter: Shooter,
-    pub(crate) key: Option<String>,
+    pub(crate) input: InputState,
+    pub(crate) score: Score,
     pub(crate) bullets: Vec<Bullet>,
+    /// how the shooter fires, before any power-up
+    pub(crate) weapon: Weapon,
+    /// started on the player's last shot, `None` if there wasn't one this wave
+    pub(crate) weapon_timer: Option<Timer>,
     pub(crate) speedster: Speedster,
     pub(crate) engine: Rc<RefCell<Engine>>,
+    /// layer under the plane for effects, never collided with
+    pub(crate) effects_layer: LayerId,
+    /// layer over the plane for text, never collided with
+    pub(crate) overlay_layer: LayerId,
+    pub(crate) renderer: Rc<RefCell<dyn Renderer>>,
     pub(crate) width: usize,
-    pub(crate) game_won: bool,
     pub(crate) game_quit: bool,
     pub(crate) game_paused: bool,
     pub(crate) game_initialized: bool,
+    /// wave the player is fighting
+    pub(crate) wave: Wave,
+    /// started when the current wave began
+    pub(crate) wave_timer: Timer,
+    /// decides when and which aliens fire
+    pub(crate) fire_control: FireControl,
+    /// every random number of the game comes from here
+    pub(crate) rng: Rng,
+    /// started when the last diver broke off from the formation
+    pub(crate) dive_timer: Timer,
+    pub(crate) high_scores: HighScores,
+    /// if true, the high scores are shown instead of the title screen
+    pub(crate) showing_high_scores: bool,
 }
 
 impl SpaceInvaders {
     pub fn new(dimensions: (usize, usize)) -> Result<Self, Error> {
-        let engine = Engine::new(dimensions).as_rc();
-        let width = { engine.borrow().width };
+        let mut engine = Engine::new(dimensions);
+        let effects_layer = engine.add_layer(-1, false);
+        let overlay_layer = engine.add_layer(1, false);
+        let engine = engine.as_rc();
+        let (width, clock) = {
+            let eng = engine.borrow();
+            (eng.width, eng.clock())
+        };
+        let wave = Wave::first();
         let shooter: Shooter = {
             let position: Vec<Coordinate> = {
                 let eng = engine.borrow();
@@ -66,83 +139,323 @@ impl SpaceInvaders {
                     (eng.width / 2, eng.height - (eng.height / 7) - 1),
                 ]
             };
-            Shooter::new(engine.clone(), position, SHOOTER_STEP_PER_DELTA)?
+            Shooter::new(
+                engine.clone(),
+                position,
+                config::get().shooter_step_per_delta,
+            )?
         };
-        let speedster: Speedster = Speedster::new(engine.clone(), SPEEDSTER_STEP_PER_DELTA)?;
+        let speedster: Speedster =
+            Speedster::new(engine.clone(), config::get().speedster_step_per_delta)?;
+        let aliens = spawn_aliens(engine.clone(), &wave)?;
         Ok(Self {
-            aliens: spawn_aliens(engine.clone(), ALIEN_COL_COUNT, ALIEN_STEP_PER_DELTA)?,
-            //aliens: Vec::new(),
+            formation_size: aliens.len(),
+            aliens,
+            divers: Vec::new(),
+            bunkers: Vec::new(),
+            ufo: None,
+            ufo_timer: Timer::start(&clock),
+            bonus_label: None,
+            capsules: Vec::new(),
+            power_ups: PowerUps::new(),
+            explosions: Vec::new(),
             alien_xd: 0.0,
+            accumulator: 0.0,
             engine,
+            effects_layer,
+            overlay_layer,
+            renderer: Rc::new(RefCell::new(TerminalRenderer::new(
+                PIXEL_CHAR,
+                BACKGROUND_CHAR,
+            ))),
             width,
             game_quit: false,
             shooter,
             alien_direction: Direction::Right,
-            key: None,
+            input: InputState::new(),
+            score: Score::new(),
             speedster,
-            game_won: false,
             game_over: false,
             game_paused: true,
             game_initialized: false,
+            wave,
+            wave_timer: Timer::start(&clock),
+            fire_control: FireControl::new(&clock),
+            rng: Rng::new(Rng::random_seed()),
+            dive_timer: Timer::start(&clock),
+            high_scores: HighScores::new(),
+            showing_high_scores: false,
             bullets: Vec::new(),
+            weapon: Weapon::standard(),
+            weapon_timer: None,
         })
     }
 
+    /// Replaces the default terminal renderer, e.g. with a
+    /// `HeadlessRenderer` when no terminal is available
+    pub fn with_renderer(mut self, renderer: Rc<RefCell<dyn Renderer>>) -> Self {
+        self.renderer = renderer;
+        self
+    }
+
+    /// Seeds the game's random numbers, the same seed and input play out the same game
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// Setter function for the table shown from the title screen
+    pub fn with_high_scores(mut self, high_scores: HighScores) -> Self {
+        self.high_scores = high_scores;
+        self
+    }
+
     pub fn set_up(&mut self) {
-        utils::clear();
+        self.renderer.borrow_mut().clear();
         for mut alien in &mut self.aliens {
             let _ = alien.spawn();
         }
         self.shooter.spawn();
+        self.bunkers = spawn_bunkers(self.engine.clone(), self.shooter.far_top());
     }
 
-    /// Memoizes the key pressed
-    pub fn handle_input(&mut self) {
-        self.key = if let Some(key) = get_key() {
+    /// Memoizes the actions taken on a frame
+    ///
+    /// Pressed actions are kept until a simulation step consumes them, that way
+    /// a short frame that runs no step doesn't swallow a key press
+    pub fn handle_input(&mut self, mut input: InputState) {
+        if !self.game_initialized && input.is_pressed(Action::HighScores) {
+            // flipping between the title screen and the high scores
+            self.showing_high_scores = !self.showing_high_scores;
+            return;
+        }
+        if !input.nothing_pressed() {
+            self.showing_high_scores = false;
             self.game_initialized = true;
-            self.game_paused = if key == "p" { true } else { false };
-            if key == "esc" {
+            self.game_paused = input.is_pressed(Action::Pause);
+            if input.is_pressed(Action::Quit) {
                 self.game_quit = true;
             }
-            Some(key)
-        } else {
-            None
         }
+        for action in self.input.pressed() {
+            input.press(action);
+        }
+        self.input = input;
     }
 
-    /// checking key pressed and spawning bullets if a space is pressed
+    /// moving the shooter while a direction is held and spawning
+    /// bullets while fire is held, both can happen on the same step
     fn _update_upon_key_press(&mut self, delta_time: f32) {
-        if let Some(key) = &self.key {
-            match key.as_str() {
-                " " => {
-                    // spawning a bullet
-                    if let Ok(mut bullet) = Bullet::new(
-                        self.engine.clone(),
-                        self.shooter.head(),
-                        BULLET_STEP_PER_DELTA,
-                    ) {
-                        if let Ok(_) = bullet.spawn() {
-                            self.bullets.push(bullet);
-                        }
-                    }
+        if self.input.is_held(Action::Fire) && !self.shooter.is_respawning() {
+            // spawning the bullets, as fast as the weapon allows
+            self._shooter_fire();
+        }
+        let direction = match (
+            self.input.is_held(Action::MoveLeft),
+            self.input.is_held(Action::MoveRight),
+        ) {
+            (true, false) => Action::MoveLeft,
+            (false, true) => Action::MoveRight,
+            // both directions cancel each other out
+            _ => return,
+        };
+        // stepping twice, the shooter moves at double its velocity
+        for _ in 0..2 {
+            if let Some(collision) = self.shooter.step(direction, delta_time) {
+                let capsule = self.capsules.iter().position(|c| c.id() == collision.other);
+                if let Some(c) = capsule {
+                    // moved onto a capsule, catching it
+                    self._catch_capsule(c);
+                    continue;
                 }
-                "left" | "right" => {
-                    let _ = self.shooter.step(&key, delta_time);
-                    if let Some(coordinate) = self.shooter.step(&key, delta_time) {
-                        self.game_over = true;
-                    }
+                // the shooter ran into a bullet or an obstacle
+                self._hit_shooter();
+                if let Some(i) = self._find_bullet(collision.other) {
+                    self.bullets[i].destroy();
+                }
+                return;
+            }
+        }
+    }
+
+    /// Spawns the player's bullets above the shooter, side by side if the
+    /// weapon fires more than one a shot, as long as the weapon allows it
+    fn _shooter_fire(&mut self) {
+        let clock = { self.engine.borrow().clock() };
+        let weapon = self.weapon.upgraded(&self.power_ups, &clock);
+        let in_flight = self
+            .bullets
+            .iter()
+            .filter(|b| !b.is_destroyed() && !b.is_alien_bullet())
+            .count();
+        let since_last_shot = self.weapon_timer.map(|timer| timer.elapsed(&clock));
+        if !weapon.can_fire(in_flight, since_last_shot) {
+            return;
+        }
+        self.weapon_timer = Some(Timer::start(&clock));
+        let (x, y) = self.shooter.head();
+        let n = weapon.bullets_per_shot;
+        let xs = (0..n).filter_map(|i| (x + i).checked_sub(n / 2));
+        for x in xs.filter(|x| *x < self.width) {
+            if let Ok(mut bullet) =
+                Bullet::new(self.engine.clone(), (x, y), weapon.bullet_step_per_delta)
+            {
+                if bullet.spawn().is_ok() {
+                    self.bullets.push(match weapon.piercing {
+                        true => bullet.to_piercing_bullet(),
+                        false => bullet,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Costs the player a life and their power-ups, ending the game once
+    /// the lives are all gone. A shield shrugs the hit off
+    fn _hit_shooter(&mut self) {
+        let clock = { self.engine.borrow().clock() };
+        let center = self.shooter.center();
+        if self.power_ups.is_active(PowerUp::Shield, &clock) || !self.shooter.hit() {
+            return;
+        }
+        self._explode(center);
+        self.score.break_combo();
+        self.power_ups.clear();
+        if self.shooter.is_dead() {
+            self.game_over = true;
+        }
+    }
+
+    /// Moves the speedster, hit at the coordinate, on to its next stage,
+    /// awarding the stage's bonus if the player shot it
+    fn _hit_speedster(&mut self, coordinate: Coordinate, by_player: bool) {
+        self.speedster.was_hit();
+        self._explode(coordinate);
+        if by_player {
+            let stage = self.speedster.stages_completed();
+            self._score(Target::SpeedsterStage(stage));
+        }
+    }
+
+    /// Hits the alien, in the formation or diving, the collision was with.
+    /// A destroyed alien is scored, and may drop a power-up, if the player
+    /// shot it, and splits if it splits. Returns false if it wasn't with an alien
+    fn _hit_alien(&mut self, collision: Collision, by_player: bool) -> bool {
+        let coordinate = collision.coordinate;
+        let hit = match find_alien_and_hit(&mut self.aliens, collision) {
+            Some(hit) => hit,
+            None => match find_alien_and_hit(&mut self.divers, collision) {
+                Some(hit) => hit,
+                None => return false,
+            },
+        };
+        self._explode(coordinate);
+        if let AlienHit::Destroyed(alien) = hit {
+            // the halves join the formation where the alien was
+            let halves = split_alien(&alien, self.wave.alien_step_per_delta);
+            self.aliens.extend(halves);
+            if by_player {
+                self._score(Target::Alien { row: alien.row() });
+                self._drop_capsule(coordinate);
+            }
+        }
+        true
+    }
+
+    /// Drops a capsule holding a random power-up at the coordinate,
+    /// where an alien was shot down, with `powerups.drop_chance` chance
+    fn _drop_capsule(&mut self, coordinate: Coordinate) {
+        let config = config::get();
+        if !self.rng.chance(config.powerup_drop_chance) {
+            return;
+        }
+        let power_up = match self.rng.pick(&PowerUp::ALL) {
+            Some(power_up) => *power_up,
+            None => return,
+        };
+        if let Ok(mut capsule) = Capsule::new(
+            self.engine.clone(),
+            coordinate,
+            power_up,
+            config.powerup_step_per_delta,
+        ) {
+            // the halves of a split alien may be in the way
+            if capsule.spawn().is_ok() {
+                self.capsules.push(capsule);
+            }
+        }
+    }
+
+    /// Moves the falling capsules. The player catches the ones landing on the
+    /// shooter, those landing on a bullet soak it up and any other one breaks
+    fn _move_capsules(&mut self, delta_time: f32) {
+        for c in (0..self.capsules.len()).rev() {
+            if let Some(collision) = self.capsules[c].step(delta_time) {
+                if collision.other == self.shooter.id() {
+                    self._catch_capsule(c);
+                } else if let Some(i) = self._find_bullet(collision.other) {
+                    self.bullets[i].destroy();
+                } else {
+                    // landed on an alien, a bunker or an obstacle
+                    self.capsules[c].destroy();
                 }
-                _ => {}
             }
+            if self.capsules[c].is_destroyed() {
+                let _ = self.capsules.remove(c);
+            }
+        }
+    }
+
+    /// Grants the player the power-up of the capsule, which is used up
+    fn _catch_capsule(&mut self, c: usize) {
+        let clock = { self.engine.borrow().clock() };
+        let power_up = self.capsules[c].power_up();
+        self.capsules[c].destroy();
+        match power_up {
+            PowerUp::ExtraLife => self.shooter.add_life(),
+            _ => self.power_ups.activate(power_up, &clock),
+        }
+    }
+
+    /// Breaks the capsule the collision was with. Returns false if it wasn't with a capsule
+    fn _break_capsule(&mut self, collision: Collision) -> bool {
+        let capsule = self.capsules.iter_mut().find(|c| c.id() == collision.other);
+        match capsule.filter(|c| !c.is_destroyed()) {
+            Some(capsule) => {
+                capsule.destroy();
+                true
+            }
+            None => false,
         }
     }
 
+    /// Adds the target's points to the score, returning them
+    fn _score(&mut self, target: Target) -> u64 {
+        let clock = { self.engine.borrow().clock() };
+        self.score.hit(target, &clock)
+    }
+
+    /// Returns how fast the formation moves sideways, faster the fewer aliens
+    /// are left in it, up to `aliens.max_speed_multiplier` times for the last one
+    fn _formation_speed(&self) -> f32 {
+        let config = config::get();
+        // aliens split in two can leave more than the formation started with
+        let gone = self.formation_size.saturating_sub(self.aliens.len());
+        let thinned = match self.formation_size {
+            0 | 1 => 0.0,
+            size => (gone as f32 / (size - 1) as f32).min(1.0),
+        };
+        let multiplier = 1.0
+            + (config.alien_max_speed_multiplier - 1.0) * thinned.powf(config.alien_speed_curve);
+        self.wave.alien_step_per_delta * multiplier
+    }
+
     /// moving aliens
     pub fn _move_aliens(&mut self, delta_time: f32) {
         if self.aliens.is_empty() {
             return;
         }
-        let offset: f32 = ALIEN_STEP_PER_DELTA * delta_time;
+        let offset: f32 = self._formation_speed() * delta_time;
         let step = (self.alien_xd + offset) as usize - self.alien_xd as usize;
         if step == 0 {
             self.alien_xd += offset;
@@ -157,14 +470,9 @@ impl SpaceInvaders {
                 }
                 for a in (0..self.aliens.len()).rev() {
                     // making the step negative to move left
-                    if let Some(coordinate) = self.aliens[a].step(0 - step as i32) {
-                        self.aliens[a].destroy();
-                        let _ = self.aliens.remove(a);
-                        // checking for bullet collisions
-                        for i in (0..self.bullets.len()).rev() {
-                            if self.bullets[i].contains(coordinate) {
-                                self.bullets[i].destroy();
-                            }
+                    if let Some(collision) = self._step_alien(a, 0 - step as i32) {
+                        if self._resolve_alien_step(a, collision) {
+                            return;
                         }
                     }
                 }
@@ -173,11 +481,14 @@ impl SpaceInvaders {
                 if farthest_right_alien(&self.aliens).far_right() == self.width - 1 {
                     self.alien_direction = Direction::Left;
                     // moving the aliens down for more pressure
+                    let descent = config::get().alien_descent as i32;
                     for a in 0..self.aliens.len() {
-                        match self.aliens[a].move_y(1) {
+                        match self._drop_alien(a, descent) {
                             Ok(state) => match state {
-                                State::Collided(coordinate) => {
-                                    if self.shooter.contains(coordinate) {
+                                State::Collided { other, .. } => {
+                                    if other == self.shooter.id() {
+                                        // the aliens reached the player, no
+                                        // matter the lives left it's over
                                         self.game_over = true;
                                         return;
                                     }
@@ -185,7 +496,11 @@ impl SpaceInvaders {
                                 _ => (),
                             },
                             Err(error) => match error.kind() {
-                                ErrorKind::OutOfBounds => todo!(),
+                                ErrorKind::OutOfBounds => {
+                                    // the aliens reached the bottom of the plane
+                                    self.game_over = true;
+                                    return;
+                                }
                                 _ => (),
                             },
                         }
@@ -194,50 +509,299 @@ impl SpaceInvaders {
                 }
                 for a in (0..self.aliens.len()).rev() {
                     // making the step negative to move left
-                    if let Some(coordinate) = self.aliens[a].step(step as i32) {
-                        self.aliens[a].destroy();
-                        let _ = self.aliens.remove(a);
-                        // checking for bullet collisions
-                        for i in (0..self.bullets.len()).rev() {
-                            if self.bullets[i].contains(coordinate) {
-                                self.bullets[i].destroy();
-                                let _ = self.bullets.remove(i);
-                            }
+                    if let Some(collision) = self._step_alien(a, step as i32) {
+                        if self._resolve_alien_step(a, collision) {
+                            return;
                         }
                     }
                 }
             }
         }
-        // getting the aliens to shoot at the player
-        let alien_in_same_x: &Alien = {
-            let shooter_xs = self.shooter.xs();
-            let mut aliens_with_same_x: Vec<&Alien> = Vec::new();
-            for alien in self.aliens.iter() {
-                //if shooter_x == alien.x() {
-                if shooter_xs.contains(&alien.x()) {
-                    aliens_with_same_x.push(alien);
+    }
+
+    /// Resolves what an alien of the formation ran into stepping sideways. A bullet
+    /// hits it as if it had flown into the alien, anything else holds it in place.
+    /// Returns true if it ran into the player, which ends the game
+    fn _resolve_alien_step(&mut self, a: usize, collision: Collision) -> bool {
+        if collision.other == self.shooter.id() {
+            // the aliens reached the player, no matter the lives left it's over
+            self.game_over = true;
+            return true;
+        }
+        if let Some(i) = self._find_bullet(collision.other) {
+            let by_player = !self.bullets[i].is_alien_bullet();
+            let hit = Collision {
+                coordinate: collision.coordinate,
+                other: self.aliens[a].id(),
+            };
+            self._hit_alien(hit, by_player);
+            if !self.bullets[i].is_piercing() {
+                self.bullets[i].destroy();
+                let _ = self.bullets.remove(i);
+            }
+        }
+        false
+    }
+
+    /// Steps an alien of the formation sideways, plowing through any bunker or
+    /// capsule in its way. Returns the collision with anything else it ran into
+    fn _step_alien(&mut self, a: usize, step: i32) -> Option<Collision> {
+        loop {
+            match self.aliens[a].step(step) {
+                Some(collision)
+                    if self._erode_bunkers(collision) || self._break_capsule(collision) =>
+                {
+                    continue
                 }
+                other => return other,
             }
-            if aliens_with_same_x.is_empty() {
-                // exits the function
-                return;
+        }
+    }
+
+    /// Moves an alien of the formation down, plowing through any bunker or capsule in its way
+    fn _drop_alien(&mut self, a: usize, step: i32) -> Result<State, Error> {
+        loop {
+            match self.aliens[a].move_y(step)? {
+                State::Collided { coordinate, other } => {
+                    let collision = Collision { coordinate, other };
+                    if !self._erode_bunkers(collision) && !self._break_capsule(collision) {
+                        return Ok(State::Collided { coordinate, other });
+                    }
+                }
+                state => return Ok(state),
             }
-            let mut farthest_down: &Alien = &aliens_with_same_x[0];
-            for alien in aliens_with_same_x.iter() {
-                if alien.far_bottom() > farthest_down.far_bottom() {
-                    farthest_down = alien
+        }
+    }
+
+    /// Moves a diving alien, plowing through any bunker or capsule in its way
+    fn _dive(&mut self, d: usize, target: usize, delta_time: f32) -> Result<State, Error> {
+        let mut state = self.divers[d].dive(target, delta_time)?;
+        loop {
+            match state {
+                State::Collided { coordinate, other } => {
+                    let collision = Collision { coordinate, other };
+                    if !self._erode_bunkers(collision) && !self._break_capsule(collision) {
+                        return Ok(state);
+                    }
+                    state = self.divers[d].resume_dive(target, coordinate)?;
                 }
+                state => return Ok(state),
             }
-            farthest_down
+        }
+    }
+
+    /// Knocks the pixel the collision was at out of the bunker it was
+    /// with. Returns false if it wasn't with a bunker
+    fn _erode_bunkers(&mut self, collision: Collision) -> bool {
+        self.bunkers
+            .iter_mut()
+            .find(|bunker| bunker.id() == collision.other)
+            .is_some_and(|bunker| bunker.erode(collision.coordinate))
+    }
+
+    /// Returns the index of the bullet on the plane with the id, if there's one
+    fn _find_bullet(&self, id: EntityId) -> Option<usize> {
+        self.bullets
+            .iter()
+            .position(|b| !b.is_destroyed() && b.id() == id)
+    }
+
+    /// Lets the aliens fire at the player, as the fire control allows
+    fn _alien_fire(&mut self) {
+        let clock = { self.engine.borrow().clock() };
+        let in_flight = self
+            .bullets
+            .iter()
+            .filter(|b| !b.is_destroyed() && b.is_alien_bullet())
+            .count();
+        let head = match self.fire_control.fire(
+            &mut self.aliens,
+            &self.shooter.xs(),
+            in_flight,
+            &self.wave,
+            &mut self.rng,
+            &clock,
+        ) {
+            Some(head) => head,
+            None => return,
         };
         if let Ok(mut b) = Bullet::new(
             self.engine.clone(),
-            alien_in_same_x.head(),
-            BULLET_STEP_PER_DELTA,
+            head,
+            config::get().bullet_step_per_delta,
         ) {
-            let _ = b.spawn();
-            self.bullets.push(b.to_alien_bullet());
+            if b.spawn().is_ok() {
+                self.bullets.push(b.to_alien_bullet());
+            }
+        }
+    }
+
+    /// Every `aliens.dive_interval` seconds, breaks the diving alien of the
+    /// formation's front line closest to the player off to dive at them
+    fn _launch_diver(&mut self) {
+        let clock = { self.engine.borrow().clock() };
+        if self.dive_timer.elapsed(&clock) < config::get().alien_dive_interval {
+            return;
+        }
+        let target = self.shooter.head().0;
+        // an alien with another one below it would run right into it
+        let diver = front_line(&self.aliens)
+            .into_iter()
+            .filter(|&a| *self.aliens[a].behaviour() == Behaviour::Dive)
+            .min_by_key(|&a| self.aliens[a].head().0.abs_diff(target));
+        if let Some(a) = diver {
+            let mut alien = self.aliens.remove(a);
+            alien.start_dive(config::get().alien_dive_step_per_delta);
+            self.divers.push(alien);
+            self.dive_timer.reset(&clock);
+        }
+    }
+
+    /// Moves the diving aliens down at the player. A diver crashing into
+    /// the player costs them a life, one that misses flies off the plane
+    fn _move_divers(&mut self, delta_time: f32) {
+        let target = self.shooter.head().0;
+        for d in (0..self.divers.len()).rev() {
+            match self._dive(d, target, delta_time) {
+                Ok(State::Collided { coordinate, other }) => {
+                    if other == self.shooter.id() {
+                        self._hit_shooter();
+                        self.divers[d].destroy();
+                        let _ = self.divers.remove(d);
+                        continue;
+                    }
+                    if let Some(i) = self._find_bullet(other) {
+                        // flying into a bullet, only the player's harm it
+                        self.bullets[i].destroy();
+                        let bottom = self.divers[d].far_bottom();
+                        if self.bullets[i].is_alien_bullet() {
+                            continue;
+                        }
+                        self._explode((coordinate.0, bottom));
+                        if self.divers[d].hit((coordinate.0, bottom)) {
+                            let alien = self.divers.remove(d);
+                            self._score(Target::Alien { row: alien.row() });
+                            self._drop_capsule((coordinate.0, bottom));
+                        }
+                    }
+                }
+                Err(_) => {
+                    // reached the bottom of the plane
+                    self.divers[d].destroy();
+                    let _ = self.divers.remove(d);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Sends a mystery UFO across the top of the plane every `ufo.interval`
+    /// seconds while the formation is alive, and moves the one flying
+    fn _move_ufo(&mut self, delta_time: f32) {
+        let clock = { self.engine.borrow().clock() };
+        if let Some(label) = self.bonus_label.as_mut() {
+            if label.is_expired(&clock) {
+                label.destroy();
+                self.bonus_label = None;
+            }
+        }
+        if self.aliens.is_empty() {
+            // the UFO only comes while the formation is alive
+            if let Some(mut ufo) = self.ufo.take() {
+                if !ufo.is_destroyed() {
+                    ufo.destroy();
+                }
+                self.ufo_timer.reset(&clock);
+            }
+            return;
+        }
+        if self.ufo.is_none() {
+            if self.ufo_timer.elapsed(&clock) < config::get().ufo_interval {
+                return;
+            }
+            let direction = match self.rng.chance(0.5) {
+                true => Direction::Left,
+                false => Direction::Right,
+            };
+            let mut ufo = match Ufo::new(
+                self.engine.clone(),
+                direction,
+                config::get().ufo_step_per_delta,
+            ) {
+                Ok(ufo) => ufo,
+                Err(_) => return,
+            };
+            if ufo.spawn().is_err() {
+                // something is in the way, trying again on the next step
+                return;
+            }
+            self.ufo = Some(ufo);
+        }
+        if let Some(collision) = self.ufo.as_mut().and_then(|ufo| ufo.step(delta_time)) {
+            let bullet = self
+                ._find_bullet(collision.other)
+                .filter(|&i| !self.bullets[i].is_alien_bullet());
+            if let Some(i) = bullet {
+                // flew right into one of the player's bullets
+                self.bullets[i].destroy();
+                self._hit_ufo();
+            }
+        }
+        if self.ufo.as_ref().is_some_and(|ufo| ufo.is_destroyed()) {
+            // shot down or flown off the plane
+            self.ufo = None;
+            self.ufo_timer.reset(&clock);
+        }
+    }
+
+    /// Shoots the UFO down, awarding a random bonus that's shown where it was
+    fn _hit_ufo(&mut self) {
+        let center = match self.ufo.as_mut() {
+            Some(ufo) if !ufo.is_destroyed() => {
+                ufo.destroy();
+                ufo.center()
+            }
+            _ => return,
+        };
+        let bonus = self
+            .rng
+            .pick(&config::get().score_ufo)
+            .copied()
+            .unwrap_or(0);
+        self._explode(center);
+        let points = self._score(Target::Ufo { points: bonus });
+        if let Some(mut label) = self.bonus_label.take() {
+            label.destroy();
+        }
+        self.bonus_label = Some(BonusLabel::new(
+            self.engine.clone(),
+            self.overlay_layer,
+            points,
+            center,
+        ));
+    }
+
+    /// Plays an explosion at the coordinate
+    fn _explode(&mut self, coordinate: Coordinate) {
+        self.explosions.push(Explosion::new(
+            self.engine.clone(),
+            self.effects_layer,
+            coordinate,
+        ));
+    }
+
+    /// Plays the explosions on, dropping the finished ones
+    fn _update_explosions(&mut self) {
+        let clock = { self.engine.borrow().clock() };
+        for explosion in self.explosions.iter_mut() {
+            explosion.update(&clock);
         }
+        self.explosions.retain(|explosion| !explosion.is_finished());
+    }
+
+    /// Returns true once every alien of the wave, diving or not, is gone
+    fn _aliens_cleared(&self) -> bool {
+        self.aliens.is_empty() && self.divers.is_empty()
     }
 
     /// moving all bullets
@@ -246,41 +810,52 @@ impl SpaceInvaders {
             if self.bullets[i].is_destroyed() {
                 continue;
             }
-            if let Some(coordinate) = self.bullets[i].step(delta_time) {
-                // coordinate of the sprite it collided with
-                //if self.aliens.find_and_destroy(coordinate) {
-                if find_alien_and_destroy(&mut self.aliens, coordinate) {
+            if let Some(collision) = self.bullets[i].step(delta_time) {
+                let (coordinate, other) = (collision.coordinate, collision.other);
+                let is_alien_bullet = self.bullets[i].is_alien_bullet();
+                if self._hit_alien(collision, !is_alien_bullet) {
+                    if !self.bullets[i].is_piercing() {
+                        self.bullets[i].destroy();
+                    }
+                } else if self.capsules.iter().any(|c| c.id() == other) {
+                    // capsules soak up the bullets running into them
+                    self.bullets[i].destroy();
+                } else if self.ufo.as_ref().is_some_and(|ufo| ufo.id() == other) {
+                    if !is_alien_bullet {
+                        self._hit_ufo();
+                    }
+                    self.bullets[i].destroy();
+                } else if self._erode_bunkers(collision) {
+                    // the bullet takes a bite out of the bunker
                     self.bullets[i].destroy();
-                } else if self.shooter.contains(coordinate) {
+                } else if other == self.shooter.id() {
                     // an alien bullet that hit the player
-                    self.game_over = true;
-                    return;
-                } else if self.speedster.contains(coordinate) {
+                    self._hit_shooter();
+                    self.bullets[i].destroy();
+                } else if other == self.speedster.id() {
                     // speedster vs the player in the end game
-                    //self.game_over = true;
-                    //self.speedster.destroy();
-                    if !self.bullets[i].is_alien_bullet() {
-                        self.speedster.was_hit();
-                    }
-                    if self.speedster.is_dead() {
-                        self.game_won = true;
+                    if !is_alien_bullet {
+                        self._hit_speedster(coordinate, true);
                     }
                     self.bullets[i].destroy();
-                } else {
-                    // collided with another bullet
-                    for a in 0..self.bullets.len() {
-                        if self.bullets[a].is_destroyed() {
-                            continue;
-                        }
-                        let mut bullet = &mut self.bullets[a];
-                        if bullet.contains(coordinate) && bullet.is_alien_bullet() {
-                            bullet.destroy();
-                            // self.bullets[i].destroy();
+                } else if !is_alien_bullet && self.speedster.shoot_down_snowflake(other) {
+                    self.bullets[i].destroy();
+                    self._score(Target::Snowflake);
+                } else if let Some(a) = self._find_bullet(other) {
+                    // collided with another bullet, the player's
+                    // shooting down the aliens' but never the other way
+                    if self.bullets[a].is_alien_bullet() {
+                        self.bullets[a].destroy();
+                        if !is_alien_bullet {
+                            self._score(Target::AlienBullet);
                         }
+                    } else if is_alien_bullet {
+                        // an alien bullet that ran into one of the player's
+                        self._score(Target::AlienBullet);
                     }
-                    // getting bullets to despawn upon contact with anything
-                    // instead of stacking. The destroy() call must be located
-                    // in this line.
+                    self.bullets[i].destroy();
+                } else {
+                    // ran into one of the speedster's obstacles
                     self.bullets[i].destroy();
                 }
             }
@@ -292,66 +867,46 @@ impl SpaceInvaders {
         }
     }
 
-    fn __if_sprite_contained_coordinate_destroyed(&mut self, coordinate: Coordinate) -> bool {
-        if find_alien_and_destroy(&mut self.aliens, coordinate) {
+    /// Resolves what the speedster, or one of its obstacles, ran into.
+    /// Returns false if it was none of the entities it can harm
+    fn _resolve_speedster_collision(&mut self, collision: Collision) -> bool {
+        let (coordinate, other) = (collision.coordinate, collision.other);
+        if find_alien_and_destroy(&mut self.aliens, other).is_some() {
             // function already does the destroying
+            self._explode(coordinate);
             return true;
-        } else if self.shooter.contains(coordinate) {
-            // an alien bullet that hit the player
-            self.game_over = true;
+        } else if self._erode_bunkers(collision) || self._break_capsule(collision) {
+            // the speedster or one of its obstacles ran into a bunker or a capsule
+            return true;
+        } else if other == self.shooter.id() {
+            // an alien bullet or an obstacle that hit the player
+            self._hit_shooter();
             return true;
-        } else if self.speedster.contains(coordinate) {
-            // speedster vs the player in the end game
-            //self.game_over = true;
-            //self.speedster.destroy();
-            self.speedster.was_hit();
-            self.game_won = true;
+        } else if other == self.speedster.id() {
+            // one of its obstacles ran into the speedster, not
+            // something the player earns points for
+            self._hit_speedster(coordinate, false);
             return true;
-        } else {
+        } else if let Some(a) = self._find_bullet(other) {
             // collided with bullet
-            for a in 0..self.bullets.len() {
-                if self.bullets[a].is_destroyed() {
-                    continue;
-                }
-                if self.bullets[a].contains(coordinate) {
-                    self.bullets[a].destroy();
-                    return true;
-                }
+            self.bullets[a].destroy();
+            if self.speedster.stage_3_phase() == 1 && !self.bullets[a].is_alien_bullet() {
+                // a snowflake fell on one of the player's bullets
+                self._score(Target::Snowflake);
             }
+            return true;
         }
         false
     }
 
-    /// Spawns the speedster once all aliens have been killed
+    /// Spawns the speedster once all aliens have been killed on a boss wave
     pub fn _spawn_speedster_if_end_game(&mut self, delta_time: f32) {
-        if !self.aliens.is_empty() {
+        if !self._aliens_cleared() || !self.wave.boss || self.speedster.is_dead() {
             // not yet an end game either because
             // aliens are still alive or there are
             // bullets on the plane
             return;
         }
-        let stages = self.speedster.stages();
-        let stages_completed = self.speedster.stages_completed();
-        if [0, 1].contains(&stages_completed) {
-            println!(
-                "{:^100}",
-                format!(
-                    "SPEEDSTER LIVES: {} / {}",
-                    stages - stages_completed,
-                    stages
-                )
-            );
-        } else {
-            // in stage 3
-            match self.speedster.stage_3_phase() {
-                0 => (),
-                1 => println!("{:^100}", "Hmmmm? It seems like its snowing..."),
-                2 => println!("{:^100}", "Watch out for the falling blocks!!!"),
-                3 => println!("{:^100}", "Quick! Shoot him!"),
-                _ => panic!("Invalid phase was returned"),
-            }
-        }
-        //if !self.bullets.is_empty() && !self.speedster.is_initialized() {
         if self
             .bullets
             .iter()
@@ -363,36 +918,77 @@ impl SpaceInvaders {
             return;
         }
         self.speedster.spawn();
-        if let Some(coordinate) = self.speedster.step(delta_time, &mut self.bullets) {
-            if self.__if_sprite_contained_coordinate_destroyed(coordinate) {
-                return;
+        if let Some(collision) = self.speedster.step(delta_time, &mut self.bullets) {
+            self._resolve_speedster_collision(collision);
+        }
+    }
+
+    /// Moves on to the next wave once the formation, and
+    /// the speedster on a boss wave, have been beaten
+    fn _advance_wave_if_cleared(&mut self) {
+        if !self._aliens_cleared() || (self.wave.boss && !self.speedster.is_dead()) {
+            return;
+        }
+        let clock = { self.engine.borrow().clock() };
+        // clearing the plane for the new formation
+        for bullet in self.bullets.iter_mut() {
+            if bullet.is_spawned() {
+                bullet.destroy();
             }
-            //if self.shooter.contains(coordinate) {
-            //    self.game_over = true;
-            //    return;
-            //}
-            //self.speedster.destroy();
-            //self.speedster.reset_position();
-            //self.speedster.next_stage();
-            if self.speedster.is_dead() {
-                self.game_won = true;
-                //return;
+        }
+        self.bullets.clear();
+        for capsule in self.capsules.iter_mut() {
+            capsule.destroy();
+        }
+        self.capsules.clear();
+        if self.wave.boss {
+            // a fresh speedster for the next boss wave
+            self.speedster.despawn();
+            if let Ok(speedster) =
+                Speedster::new(self.engine.clone(), config::get().speedster_step_per_delta)
+            {
+                self.speedster = speedster;
             }
-            return;
         }
-        //if self.speedster.x() == self.shooter.x() {
-        //if self.shooter.xs().iter().any(|x| *x == self.speedster.x()) {
-        //    // sprites are in the same x position, means the sprite should shoot
-        //    if let Ok(mut b) = Bullet::new(
-        //        self.engine.clone(),
-        //        self.speedster.head(),
-        //        BULLET_STEP_PER_DELTA,
-        //    ) {
-        //        let mut bullet = b.to_alien_bullet();
-        //        let _ = bullet.spawn();
-        //        self.bullets.push(bullet);
-        //    }
-        //}
+        // fresh bunkers for the new wave
+        for bunker in self.bunkers.iter_mut() {
+            if bunker.is_spawned() {
+                bunker.destroy();
+            }
+        }
+        self.bunkers = spawn_bunkers(self.engine.clone(), self.shooter.far_top());
+        self.wave = self.wave.next();
+        self.aliens = spawn_aliens(self.engine.clone(), &self.wave).unwrap_or_default();
+        // an alien that can't be placed could never be shot down
+        self.aliens.retain_mut(|alien| alien.spawn().is_ok());
+        self.formation_size = self.aliens.len();
+        self.alien_direction = Direction::Right;
+        self.alien_xd = 0.0;
+        self.wave_timer.reset(&clock);
+        self.dive_timer.reset(&clock);
+        self.fire_control.reset(&clock);
+        self.weapon_timer = None;
+    }
+
+    /// Simulates the elapsed frame time in fixed steps of `FIXED_TIMESTEP`,
+    /// leftover time is carried over to the next frame.
+    ///
+    /// Because every step is the same length, the same keys fed with the same
+    /// frame times always produce the same game, no matter the machine.
+    pub fn tick(&mut self, frame_time: f32) {
+        // capping the frame time so a stalled terminal doesn't
+        // make the simulation spiral trying to catch up
+        self.accumulator += frame_time.min(MAX_FRAME_TIME);
+        let mut stepped = false;
+        while self.accumulator >= FIXED_TIMESTEP {
+            self.update(FIXED_TIMESTEP);
+            self.accumulator -= FIXED_TIMESTEP;
+            stepped = true;
+        }
+        if stepped {
+            // the presses were consumed by the simulation
+            self.input.clear_pressed();
+        }
     }
 
     pub fn update(&mut self, delta_time: f32) {
@@ -400,14 +996,41 @@ impl SpaceInvaders {
             return;
         }
         {
-            // sends the key to the shooter or spawns new bullets
+            // entities measure their timers against the engine's clock
+            self.engine.borrow_mut().advance_clock(delta_time);
+        }
+        {
+            // respawns the shooter after it was hit
+            self.shooter.update();
+            // drops the combo once too long passed since the last hit
+            let clock = { self.engine.borrow().clock() };
+            self.score.update(&clock);
+        }
+        {
+            // moves the shooter or spawns new bullets
             self._update_upon_key_press(delta_time);
         }
         {
-            //// moves aliens, taking into account collisions with bullets,
-            //// if collides with a bullet, both the alien and bullet get destroyed
+            // moves aliens, taking into account collisions with bullets,
+            // the player and anything else in their way
             self._move_aliens(delta_time);
-            //self.aliens = Vec::new();
+        }
+        {
+            // the aliens shoot back
+            self._alien_fire();
+        }
+        {
+            // diving aliens break off from the formation and swoop at the player
+            self._launch_diver();
+            self._move_divers(delta_time);
+        }
+        {
+            // a mystery UFO crosses the top of the plane every now and then
+            self._move_ufo(delta_time);
+        }
+        {
+            // power-ups dropped by the aliens fall towards the player
+            self._move_capsules(delta_time);
         }
         {
             // If all aliens are dead, then this sprite will spawn
@@ -422,26 +1045,461 @@ impl SpaceInvaders {
             // a win for the player.
             self._move_bullets(delta_time);
         }
+        {
+            // debris of the hits plays out
+            self._update_explosions();
+        }
+        {
+            // once the wave is beaten, the next one comes
+            self._advance_wave_if_cleared();
+        }
+    }
+
+    /// Returns the end game message describing the speedster's progress
+    fn _end_game_status(&self) -> Option<String> {
+        if !self._aliens_cleared() || !self.wave.boss {
+            return None;
+        }
+        let stages = self.speedster.stages();
+        let stages_completed = self.speedster.stages_completed();
+        if [0, 1].contains(&stages_completed) {
+            return Some(self.center(&format!(
+                "SPEEDSTER LIVES: {} / {}",
+                stages - stages_completed,
+                stages
+            )));
+        }
+        // in stage 3
+        match self.speedster.stage_3_phase() {
+            0 => None,
+            1 => Some(self.center("Hmmmm? It seems like its snowing...")),
+            2 => Some(self.center("Watch out for the falling blocks!!!")),
+            3 => Some(self.center("Quick! Shoot him!")),
+            _ => panic!("Invalid phase was returned"),
+        }
+    }
+
+    /// Returns the line always shown above the plane, the score, combo and
+    /// active power-ups on the left and the wave and lives on the right
+    fn _hud(&self) -> String {
+        let clock = { self.engine.borrow().clock() };
+        let mut left = format!("SCORE: {}", self.score.points());
+        if self.score.multiplier() > 1 {
+            left.push_str(&format!("  COMBO x{}", self.score.multiplier()));
+        }
+        for (power_up, time_left) in self.power_ups.active(&clock) {
+            left.push_str(&format!(
+                "  {} {}s",
+                power_up.name(),
+                time_left.ceil() as u64
+            ));
+        }
+        let right = format!(
+            "WAVE: {}  LIVES: {}",
+            self.wave.number,
+            self.shooter.lives()
+        );
+        let gap = self.width.saturating_sub(left.len() + right.len()).max(1);
+        self.fit(&format!("{}{}{}", left, " ".repeat(gap), right))
+    }
+
+    /// Centers a message over the plane, cutting it short if it is wider
+    /// than the plane so it never wraps into the next line
+    fn center(&self, message: &str) -> String {
+        let message: String = message.chars().take(self.width).collect();
+        format!("{:^width$}", message, width = self.width)
+    }
+
+    /// Cuts a message short if it is wider than the plane
+    fn fit(&self, message: &str) -> String {
+        message.chars().take(self.width).collect()
+    }
+
+    /// Returns the high-score table laid out as a page, centered over the plane
+    fn _high_scores_page(&self) -> Vec<String> {
+        let mut page = vec![String::new(), self.center("HIGH SCORES"), String::new()];
+        let table = self.high_scores.lines();
+        // centering the table as a block so its columns stay aligned
+        let table_width = table
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let margin = " ".repeat(self.width.saturating_sub(table_width) / 2);
+        for line in table {
+            page.push(self.fit(&format!("{}{}", margin, line)));
+        }
+        let key = config::get()
+            .keys
+            .keys_for(Action::HighScores)
+            .first()
+            .map_or(String::new(), |key| format!("{} to go back, ", key));
+        page.push(String::new());
+        page.push(self.center(&format!("Press {}any other game key to start", key)));
+        page
     }
 
     pub fn draw(&mut self) {
+        if self.showing_high_scores {
+            let page = self._high_scores_page();
+            self.renderer.borrow_mut().present_page(&page);
+            return;
+        }
+        let clock = { self.engine.borrow().clock() };
+        let mut status: Vec<String> = vec![self._hud()];
         if !self.game_initialized {
-            println!("Welcome to Space Invaders! Press any game key to start!");
+            let welcome = match config::get().keys.keys_for(Action::HighScores).first() {
+                Some(key) => format!(
+                    "Welcome to Space Invaders! Press any game key to start, {} for high scores!",
+                    key
+                ),
+                None => "Welcome to Space Invaders! Press any game key to start!".to_string(),
+            };
+            status.push(self.fit(&welcome));
         } else if self.game_paused {
-            println!("Game is paused. Press any game key to continue...");
+            status.push(self.fit("Game is paused. Press any game key to continue..."));
         }
-        print!(
-            "{}",
-            self.engine.borrow().display(PIXEL_CHAR, BACKGROUND_CHAR)
-        );
-        utils::refresh();
+        if let Some(message) = self._end_game_status() {
+            status.push(message);
+        } else if self.game_initialized && self.wave_timer.elapsed(&clock) < WAVE_BANNER_DURATION {
+            let banner = match self.wave.boss {
+                true => format!(
+                    "WAVE {}, the speedster awaits at the end!",
+                    self.wave.number
+                ),
+                false => format!("WAVE {}", self.wave.number),
+            };
+            status.push(self.center(&banner));
+        }
+        status.resize(STATUS_LINES, String::new());
+        self.renderer
+            .borrow_mut()
+            .present(&mut self.engine.borrow_mut(), &status);
+    }
+
+    /// Returns false while the renderer asks for a bigger surface,
+    /// the game shouldn't be ticked meanwhile
+    pub fn fits(&self) -> bool {
+        let height = { self.engine.borrow().height };
+        self.renderer
+            .borrow()
+            .fits(self.width, height + STATUS_LINES)
     }
 
     pub fn game_over(&mut self) -> bool {
-        (self.aliens.is_empty() && self.speedster.is_dead()) || self.game_over || self.game_quit
+        self.game_over || self.game_quit
+    }
+
+    /// Returns the points scored so far
+    pub fn score(&self) -> u64 {
+        self.score.points()
+    }
+
+    /// Returns the wave the player reached
+    pub fn wave(&self) -> usize {
+        self.wave.number
+    }
+
+    /// Returns the seconds played, pauses excluded
+    pub fn play_time(&self) -> f64 {
+        self.engine.borrow().clock().now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::renderer::{Frame, HeadlessRenderer};
+
+    /// Starts a seeded game on a 60x24 plane, drawn by a headless renderer
+    fn start() -> (SpaceInvaders, Rc<RefCell<HeadlessRenderer>>) {
+        let renderer = Rc::new(RefCell::new(HeadlessRenderer::new('#', '.')));
+        let mut game = SpaceInvaders::new((60, 24))
+            .unwrap()
+            .with_renderer(renderer.clone())
+            .with_seed(1);
+        game.set_up();
+        game.game_initialized = true;
+        game.game_paused = false;
+        (game, renderer)
     }
 
-    pub fn won(&self) -> bool {
-        self.game_won
+    /// Runs the given number of fixed steps, then draws a frame
+    fn run(game: &mut SpaceInvaders, steps: usize) {
+        for _ in 0..steps {
+            game.tick(FIXED_TIMESTEP);
+        }
+        game.draw();
+    }
+
+    /// Shoots down the whole formation of a boss wave, letting the speedster in
+    fn clear_boss_wave(game: &mut SpaceInvaders) {
+        game.aliens.iter_mut().for_each(|alien| alien.destroy());
+        game.aliens.clear();
+        game.wave.boss = true;
+    }
+
+    fn rows(frame: &Frame, from: usize, to: usize) -> Vec<&str> {
+        frame.rows[from..to].iter().map(String::as_str).collect()
+    }
+
+    const FORMATION: [&str; 10] = [
+        "⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......",
+        "⨊.⨊.......⨊.⨊.......⨊.⨊.......⨊.⨊.......⨊.⨊.......⨊.⨊.......",
+        "............................................................",
+        "............................................................",
+        "⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......",
+        "⨊.⨊.......⨊.⨊.......⨊.⨊.......⨊.⨊.......⨊.⨊.......⨊.⨊.......",
+        "............................................................",
+        "............................................................",
+        "⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......⨊⨊⨊.......",
+        "⨊.⨊.......⨊.⨊.......⨊.⨊.......⨊.⨊.......⨊.⨊.......⨊.⨊.......",
+    ];
+
+    const BUNKERS: [&str; 3] = [
+        "..........▓▓▓▓▓.......▓▓▓▓▓.......▓▓▓▓▓.......▓▓▓▓▓.........",
+        ".........▓▓▓▓▓▓▓.....▓▓▓▓▓▓▓.....▓▓▓▓▓▓▓.....▓▓▓▓▓▓▓........",
+        ".........▓▓...▓▓.....▓▓...▓▓.....▓▓...▓▓.....▓▓...▓▓........",
+    ];
+
+    #[test]
+    fn first_frame_shows_the_formation_bunkers_and_shooter() {
+        let (mut game, renderer) = start();
+        game.draw();
+
+        let renderer = renderer.borrow();
+        let frame = renderer.last_frame().unwrap();
+        assert_eq!(
+            frame.status[0],
+            format!("SCORE: 0{}WAVE: 1  LIVES: 3", " ".repeat(35))
+        );
+        assert_eq!(frame.status[1].trim(), "WAVE 1");
+        assert_eq!(rows(frame, 4, 14), FORMATION);
+        assert_eq!(rows(frame, 14, 17), BUNKERS);
+        assert_eq!(frame.cell(30, 20), Some('█'));
+        assert_eq!(
+            rows(frame, 21, 22),
+            [".............................███............................"]
+        );
+    }
+
+    #[test]
+    fn aliens_march_to_the_right() {
+        let (mut game, renderer) = start();
+        run(&mut game, 30);
+
+        let frames = renderer.borrow_mut().take_frames();
+        assert_eq!(frames.len(), 1);
+        let marched: Vec<String> = FORMATION
+            .iter()
+            .map(|row| {
+                let chars: Vec<char> = row.chars().collect();
+                let (kept, wrapped) = chars.split_at(chars.len() - 7);
+                wrapped.iter().chain(kept).collect()
+            })
+            .collect();
+        assert_eq!(frames[0].rows[4..14], marched);
+        assert!(renderer.borrow().frames().is_empty());
+    }
+
+    #[test]
+    fn first_shot_goes_off_right_away() {
+        let (mut game, renderer) = start();
+        game._shooter_fire();
+        game.draw();
+
+        let renderer = renderer.borrow();
+        let frame = renderer.last_frame().unwrap();
+        assert_eq!(game.bullets.len(), 1);
+        assert_ne!(frame.cell(30, 19), Some('.'));
+    }
+
+    #[test]
+    fn speedster_enters_once_a_boss_wave_is_cleared() {
+        let (mut game, renderer) = start();
+        clear_boss_wave(&mut game);
+        run(&mut game, 1);
+
+        let renderer = renderer.borrow();
+        let frame = renderer.last_frame().unwrap();
+        assert_eq!(frame.status[1].trim(), "SPEEDSTER LIVES: 3 / 3");
+        assert_eq!(
+            rows(frame, 0, 2),
+            [
+                "............................................................",
+                "........................................................≡≡≡.",
+            ]
+        );
+        assert!(frame.rows[4..14].iter().all(|row| !row.contains('⨊')));
+        assert_eq!(rows(frame, 14, 17), BUNKERS);
+    }
+
+    #[test]
+    fn speedster_loses_a_life_per_stage() {
+        let (mut game, renderer) = start();
+        clear_boss_wave(&mut game);
+        run(&mut game, 1);
+        game.speedster.was_hit();
+        run(&mut game, 1);
+        game.speedster.was_hit();
+        run(&mut game, 1);
+
+        let renderer = renderer.borrow();
+        let lives: Vec<&str> = renderer
+            .frames()
+            .iter()
+            .map(|frame| frame.status[1].trim())
+            .collect();
+        assert_eq!(
+            lives[..2],
+            ["SPEEDSTER LIVES: 3 / 3", "SPEEDSTER LIVES: 2 / 3"]
+        );
+        assert_eq!(game.speedster.stages_completed(), 2);
+    }
+
+    #[test]
+    fn speedster_hides_in_a_guard_while_it_snows() {
+        let (mut game, renderer) = start();
+        clear_boss_wave(&mut game);
+        run(&mut game, 1);
+        game.speedster.was_hit();
+        run(&mut game, 1);
+        game.speedster.was_hit();
+        run(&mut game, 240);
+
+        let renderer = renderer.borrow();
+        let frame = renderer.last_frame().unwrap();
+        assert_eq!(
+            frame.status[1].trim(),
+            "Hmmmm? It seems like its snowing..."
+        );
+        assert_eq!(
+            rows(frame, 0, 4),
+            [
+                ".........................▒.........▒........................",
+                ".........................▒...≡≡≡...▒........................",
+                ".........................▒.........▒........................",
+                ".........................▒▒▒▒▒▒▒▒▒▒▒........................",
+            ]
+        );
+        assert_eq!(
+            rows(frame, 5, 6),
+            [".......▒......▒......▒......▒......▒......▒......▒......▒..."]
+        );
+        assert_eq!(
+            rows(frame, 11, 12),
+            [".........▒........▒........▒........▒........▒........▒....."]
+        );
+        assert_eq!(rows(frame, 14, 17), BUNKERS);
+    }
+
+    #[test]
+    fn blocks_drop_on_the_bunkers_after_the_snow() {
+        let (mut game, renderer) = start();
+        clear_boss_wave(&mut game);
+        run(&mut game, 1);
+        game.speedster.was_hit();
+        run(&mut game, 1);
+        game.speedster.was_hit();
+        while game.speedster.stage_3_phase() < 2 {
+            game.tick(FIXED_TIMESTEP);
+        }
+        run(&mut game, 60);
+
+        let renderer = renderer.borrow();
+        let frame = renderer.last_frame().unwrap();
+        assert_eq!(
+            frame.status[1].trim(),
+            "Watch out for the falling blocks!!!"
+        );
+        assert_eq!(
+            rows(frame, 1, 2),
+            [".............................≡≡≡............................"]
+        );
+        assert_eq!(frame.grid()[12], vec!['▒'; 60]);
+        assert_eq!(
+            rows(frame, 14, 17),
+            [
+                "..........▓▓▓▓........▓▓▓............▓▓.......▓▓▓.▓.........",
+                "..........▓▓▓▓.▓......▓▓▓............▓▓▓......▓▓▓.▓▓........",
+                "..........▓...▓▓......▓...............▓▓......▓...▓▓........",
+            ]
+        );
+    }
+
+    #[test]
+    fn an_alien_stepping_into_a_bullet_is_shot_down() {
+        let (mut game, _) = start();
+        // a bullet hanging right next to the first alien of the top row
+        let mut bullet = Bullet::new(game.engine.clone(), (3, 4), 0.0).unwrap();
+        bullet.spawn().unwrap();
+        game.bullets.push(bullet);
+        let aliens = game.aliens.len();
+        while game.aliens.len() == aliens {
+            game.tick(FIXED_TIMESTEP);
+        }
+
+        assert!(game.score.points() > 0);
+        assert!(game.bullets.iter().all(|b| b.is_alien_bullet()));
+        assert!(!game.game_over);
+    }
+
+    #[test]
+    fn holding_fire_shoots_as_often_as_the_cooldown_allows() {
+        let (mut game, _) = start();
+        let mut input = InputState::new();
+        input.hold(Action::Fire);
+        game.handle_input(input);
+        let shots =
+            |game: &SpaceInvaders| game.bullets.iter().filter(|b| !b.is_alien_bullet()).count();
+
+        run(&mut game, 1);
+        assert_eq!(shots(&game), 1);
+        // the standard cooldown is a quarter of a second, 15 steps
+        run(&mut game, 10);
+        assert_eq!(shots(&game), 1);
+        run(&mut game, 10);
+        assert_eq!(shots(&game), 2);
+    }
+
+    #[test]
+    fn an_obstacle_running_into_the_speedster_scores_nothing() {
+        let (mut game, _) = start();
+        clear_boss_wave(&mut game);
+        run(&mut game, 1);
+        let collision = Collision {
+            coordinate: game.speedster.head(),
+            other: game.speedster.id(),
+        };
+
+        assert!(game._resolve_speedster_collision(collision));
+        assert_eq!(game.speedster.stages_completed(), 1);
+        assert_eq!(game.score.points(), 0);
+    }
+
+    #[test]
+    fn the_formation_speeds_up_as_it_thins_out() {
+        let (mut game, _) = start();
+        let base = game.wave.alien_step_per_delta;
+        let config = config::get();
+        assert_eq!(game.formation_size, 18);
+        assert_eq!(game._formation_speed(), base);
+
+        // 11 aliens to start with so half of the way is a whole alien
+        game.formation_size = 11;
+        game.aliens.truncate(6);
+        let half = 0.5f32.powf(config.alien_speed_curve);
+        let expected = base * (1.0 + (config.alien_max_speed_multiplier - 1.0) * half);
+        assert_eq!(game._formation_speed(), expected);
+
+        game.aliens.truncate(1);
+        let max = base * config.alien_max_speed_multiplier;
+        assert_eq!(game._formation_speed(), max);
+        // never any faster, however few are left
+        game.aliens.clear();
+        assert_eq!(game._formation_speed(), max);
+        // a formation of a single alien has nothing to thin out
+        game.formation_size = 1;
+        assert_eq!(game._formation_speed(), base);
     }
 }
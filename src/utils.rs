@@ -12,6 +12,16 @@
 //! - [`refresh()`]: Resets the terminal cursor position to `(0, 0)` without clearing
 //!   the screen. Useful for updating game state in-place.
 //!
+//! - [`supports_color()`]: Tells whether colors should be drawn, honoring the
+//!   `NO_COLOR` convention and dumb terminals.
+//!
+//! # Files and Dates
+//!
+//! - [`data_dir()`]: The directory the game keeps its files in, under the user's data
+//!   directory (`$XDG_DATA_HOME`, `~/Library/Application Support` or `%APPDATA%`).
+//!
+//! - [`format_date(timestamp: u64)`]: Formats a Unix timestamp as a `YYYY-MM-DD` date.
+//!
 //! # Thread Control
 //!
 //! - [`sleep(n: f64)`]: Pauses the current thread for `n` seconds (fractional allowed).
@@ -70,6 +80,59 @@ pub fn refresh() {
 //    print!("\x1B[H");
 //}
 
+/// Returns false if the user opted out of colors through `NO_COLOR`
+/// or the terminal is unable to display them
+pub fn supports_color() -> bool {
+    use std::env;
+
+    if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    if env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return false;
+    }
+    true
+}
+
+/// Returns the directory the game keeps its files in, `None`
+/// if the environment doesn't tell where the user's data goes
+pub fn data_dir() -> Option<std::path::PathBuf> {
+    use std::env;
+    use std::path::PathBuf;
+
+    let base: PathBuf = if cfg!(target_os = "windows") {
+        PathBuf::from(env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(env::var_os("HOME")?).join("Library/Application Support")
+    } else {
+        match env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(env::var_os("HOME")?).join(".local/share"),
+        }
+    };
+    Some(base.join("spaceinvaders"))
+}
+
+/// Formats seconds since the Unix epoch as a `YYYY-MM-DD` date, in UTC
+pub fn format_date(timestamp: u64) -> String {
+    // converting days since the epoch into a civil date, counting
+    // in 400 year eras that start on March 1st of the year 0
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = match month_from_march < 10 {
+        true => month_from_march + 3,
+        false => month_from_march - 9,
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
 /// Delays any thread action
 pub fn sleep(n: f64) {
     use std::thread;
@@ -96,3 +159,33 @@ pub(crate) fn log(data: String, file: String) {
     });
     let _ = callback.join();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_date_starts_at_the_epoch() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(86_399), "1970-01-01");
+        assert_eq!(format_date(86_400), "1970-01-02");
+    }
+
+    #[test]
+    fn format_date_rolls_over_months_and_years() {
+        assert_eq!(format_date(1_703_980_800), "2023-12-31");
+        assert_eq!(format_date(1_704_067_199), "2023-12-31");
+        assert_eq!(format_date(1_704_067_200), "2024-01-01");
+    }
+
+    #[test]
+    fn format_date_counts_leap_years() {
+        assert_eq!(format_date(1_709_164_800), "2024-02-29");
+        // divisible by 400, so 2000 is a leap year
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(951_868_800), "2000-03-01");
+        // divisible by 100 but not by 400, so 2100 isn't
+        assert_eq!(format_date(4_107_456_000), "2100-02-28");
+        assert_eq!(format_date(4_107_542_400), "2100-03-01");
+    }
+}
@@ -0,0 +1,89 @@
+//! Waves
+//!
+//! The game is played in waves. Every wave re-spawns the alien formation, bigger,
+//! faster and firing more often than the last one, and every few waves the
+//! speedster shows up as a boss once the formation is cleared. The game goes on,
+//! wave after wave, until the player runs out of lives.
+//!
+//! # Escalation
+//! Starting from the configured values on wave 1:
+//! - Columns: one more every wave, up to 10.
+//! - Rows: one more every other wave, up to `waves.max_rows`.
+//! - Speed: raised by `waves.speed_increase` of the first wave's every wave.
+//! - Fire rate: raised by `waves.fire_rate_increase` of the first wave's every wave.
+//! - Aggression: the alien bullets allowed in flight and the aim are raised, and
+//!   the fire cooldown lowered, by `waves.aggression_increase` of the first wave's
+//!   every wave, see [`crate::fire_control`].
+//! - Boss: the speedster follows the formation every `waves.boss_every` waves.
+//!
+//! How the rows and columns line up, and how many fit on the plane, is up to
+//! the formations, see [`crate::formation`].
+//!
+//! # Example
+//! ```rust
+//! use crate::wave::Wave;
+//!
+//! let wave = Wave::first();
+//! let next = wave.next();
+//! assert!(next.alien_step_per_delta > wave.alien_step_per_delta);
+//! ```
+use crate::config;
+
+/// Rows of the formation on the first wave
+const FIRST_WAVE_ROWS: usize = 3;
+/// Columns a formation can't go past
+const MAX_COLUMNS: usize = 10;
+
+/// How hard a wave is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wave {
+    /// starting at 1
+    pub number: usize,
+    /// rows of aliens in the formation
+    pub rows: usize,
+    /// aliens in every row of the formation
+    pub columns: usize,
+    /// how fast the formation moves sideways
+    pub alien_step_per_delta: f32,
+    /// alien shots per second, at most
+    pub fire_rate: f32,
+    /// seconds an alien waits after firing before it can fire again
+    pub fire_cooldown: f32,
+    /// most alien bullets in flight at once
+    pub max_alien_bullets: usize,
+    /// chance a shot comes from an alien right above the shooter
+    pub aim: f32,
+    /// if true, the speedster comes once the formation is cleared
+    pub boss: bool,
+}
+
+impl Wave {
+    pub fn first() -> Self {
+        Self::nth(1)
+    }
+
+    /// Returns the wave following this one
+    pub fn next(&self) -> Self {
+        Self::nth(self.number + 1)
+    }
+
+    /// Returns the given wave, starting at 1
+    pub fn nth(number: usize) -> Self {
+        let config = config::get();
+        let number = number.max(1);
+        let waves_past = (number - 1) as f32;
+        let aggression = 1.0 + config.wave_aggression_increase * waves_past;
+        Self {
+            number,
+            rows: (FIRST_WAVE_ROWS + (number - 1) / 2).min(config.wave_max_rows),
+            columns: (config.alien_col_count + number - 1).min(MAX_COLUMNS),
+            alien_step_per_delta: config.alien_step_per_delta
+                * (1.0 + config.wave_speed_increase * waves_past),
+            fire_rate: config.alien_fire_rate * (1.0 + config.wave_fire_rate_increase * waves_past),
+            fire_cooldown: config.alien_fire_cooldown / aggression,
+            max_alien_bullets: (config.alien_max_bullets as f32 * aggression).round() as usize,
+            aim: (config.alien_aim * aggression).min(1.0),
+            boss: number % config.wave_boss_every == 0,
+        }
+    }
+}
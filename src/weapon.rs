@@ -0,0 +1,166 @@
+//! Player Weapon
+//!
+//! The shooter fires through a [`Weapon`] following these rules:
+//!
+//! - Cooldown: a shot goes off at most every `shooter.fire_cooldown` seconds.
+//! - Cap: no shot goes off while it would put more than `shooter.max_bullets`
+//!   of the player's bullets on the plane.
+//! - Classic: with `shooter.classic_fire` the cap is a single bullet, like in the
+//!   arcade, the next shot only going off once the last one hit something.
+//!
+//! Power-ups upgrade the weapon while they last, see [`Weapon::upgraded`].
+//!
+//! # Example
+//! ```rust
+//! use crate::weapon::Weapon;
+//!
+//! let weapon = Weapon::standard().upgraded(&power_ups, &clock);
+//! if weapon.can_fire(in_flight, since_last_shot) {
+//!     // spawn `weapon.bullets_per_shot` bullets
+//! }
+//! ```
+use crate::config::{self, Config};
+use crate::engine::clock::Clock;
+use crate::powerups::{PowerUp, PowerUps};
+
+/// Bullets a spread shot fires side by side
+const SPREAD_BULLETS: usize = 3;
+
+/// How the shooter fires
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weapon {
+    /// seconds between two shots
+    pub cooldown: f32,
+    /// most of the player's bullets on the plane at once
+    pub max_bullets: usize,
+    /// bullets fired side by side on every shot
+    pub bullets_per_shot: usize,
+    /// how fast its bullets fly
+    pub bullet_step_per_delta: f32,
+    /// if true, its bullets go through the aliens they hit
+    pub piercing: bool,
+}
+
+impl Weapon {
+    /// Returns the weapon the shooter starts with, as configured
+    pub fn standard() -> Self {
+        Self::configured(config::get())
+    }
+
+    fn configured(config: &Config) -> Self {
+        Self {
+            cooldown: config.shooter_fire_cooldown,
+            max_bullets: match config.shooter_classic_fire {
+                true => 1,
+                false => config.shooter_max_bullets,
+            },
+            bullets_per_shot: 1,
+            bullet_step_per_delta: config.bullet_step_per_delta,
+            piercing: false,
+        }
+    }
+
+    /// Returns the weapon upgraded by the active power-ups:
+    /// - Spread: three bullets a shot, and three times the cap to match.
+    /// - Rapid: half the cooldown and bullets twice as fast.
+    /// - Piercing: bullets going through the aliens.
+    pub fn upgraded(&self, power_ups: &PowerUps, clock: &Clock) -> Self {
+        let mut weapon = *self;
+        if power_ups.is_active(PowerUp::Spread, clock) {
+            weapon.bullets_per_shot = SPREAD_BULLETS;
+            weapon.max_bullets *= SPREAD_BULLETS;
+        }
+        if power_ups.is_active(PowerUp::Rapid, clock) {
+            weapon.cooldown /= 2.0;
+            weapon.bullet_step_per_delta *= 2.0;
+        }
+        if power_ups.is_active(PowerUp::Piercing, clock) {
+            weapon.piercing = true;
+        }
+        weapon
+    }
+
+    /// Returns true if a shot can go off, given how many of the player's bullets
+    /// are on the plane and the seconds since the last shot, `None` if there wasn't one
+    pub fn can_fire(&self, in_flight: usize, since_last_shot: Option<f32>) -> bool {
+        since_last_shot.is_none_or(|seconds| seconds >= self.cooldown)
+            && in_flight + self.bullets_per_shot <= self.max_bullets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a clock the given seconds in
+    fn at(seconds: f32) -> Clock {
+        let mut clock = Clock::new();
+        clock.advance(seconds);
+        clock
+    }
+
+    #[test]
+    fn waits_for_the_cooldown() {
+        let weapon = Weapon::standard();
+        assert!(weapon.can_fire(0, None));
+        assert!(!weapon.can_fire(0, Some(weapon.cooldown / 2.0)));
+        assert!(weapon.can_fire(0, Some(weapon.cooldown)));
+    }
+
+    #[test]
+    fn never_goes_over_the_cap() {
+        let weapon = Weapon::standard();
+        assert_eq!(weapon.max_bullets, 3);
+        assert!(weapon.can_fire(2, None));
+        assert!(!weapon.can_fire(3, None));
+        assert!(!weapon.can_fire(3, Some(10.0)));
+    }
+
+    #[test]
+    fn classic_fire_is_a_single_bullet() {
+        let weapon = Weapon::configured(&Config {
+            shooter_classic_fire: true,
+            ..Config::default()
+        });
+        assert_eq!(weapon.max_bullets, 1);
+        assert!(weapon.can_fire(0, None));
+        assert!(!weapon.can_fire(1, Some(10.0)));
+    }
+
+    #[test]
+    fn spread_fires_three_bullets_with_three_times_the_cap() {
+        let clock = at(0.0);
+        let mut power_ups = PowerUps::new();
+        power_ups.activate(PowerUp::Spread, &clock);
+        let weapon = Weapon::standard().upgraded(&power_ups, &clock);
+        assert_eq!(weapon.bullets_per_shot, 3);
+        assert_eq!(weapon.max_bullets, 9);
+        // a whole shot has to fit under the cap
+        assert!(weapon.can_fire(6, None));
+        assert!(!weapon.can_fire(7, None));
+    }
+
+    #[test]
+    fn rapid_halves_the_cooldown_and_doubles_the_speed() {
+        let clock = at(0.0);
+        let mut power_ups = PowerUps::new();
+        power_ups.activate(PowerUp::Rapid, &clock);
+        let standard = Weapon::standard();
+        let weapon = standard.upgraded(&power_ups, &clock);
+        assert_eq!(weapon.cooldown, standard.cooldown / 2.0);
+        assert_eq!(
+            weapon.bullet_step_per_delta,
+            standard.bullet_step_per_delta * 2.0
+        );
+        assert!(weapon.can_fire(0, Some(standard.cooldown / 2.0)));
+    }
+
+    #[test]
+    fn piercing_only_lasts_while_the_power_up_does() {
+        let mut power_ups = PowerUps::new();
+        power_ups.activate(PowerUp::Piercing, &at(0.0));
+        let weapon = Weapon::standard();
+        assert!(weapon.upgraded(&power_ups, &at(1.0)).piercing);
+        assert_eq!(weapon.upgraded(&power_ups, &at(60.0)), weapon);
+    }
+}